@@ -33,11 +33,18 @@ fn compile_bindings() {
         .math_lib(MathLib::Fast)
         .bindgen_builder(
             builder()
+                .allowlist_function("resample_with_cached_weights_1")
+                .allowlist_function("resample_with_cached_weights_2")
                 .allowlist_function("resample_with_cached_weights_3")
                 .allowlist_function("resample_with_cached_weights_4")
+                .allowlist_function("resample_normal_map_with_cached_weights")
                 .allowlist_function("downsample_normal_map")
+                .allowlist_function("decode_normal_map")
+                .allowlist_function("average_normals")
+                .allowlist_function("write_normals")
                 .allowlist_function("calculate_weights_lanczos")
                 .allowlist_function("calculate_weight_dimensions")
+                .allowlist_function("calculate_alpha_coverage")
                 .allowlist_function("scale_to_alpha_coverage"),
         )
         .out_dir("src/ispc")
@@ -0,0 +1,289 @@
+//! Reusable downsampling plan which caches the filter weights.
+
+use crate::{
+    error::unwrap_or_panic, precompute_custom_weights, precompute_weights, resample::resample,
+    weights::Weights, AlbedoFormat, DownsampleError, DownsampleOptions, Image, ImageMut,
+    ImagePixelFormat, ResampleFilter,
+};
+
+/// Downsamples images of one resolution to another, reusing the filter weights and the intermediate buffer between calls.
+///
+/// Calculating the weights is a significant part of the cost of [`downsample_with_options()`][crate::downsample_with_options()].
+/// When many images of the same resolution have to be downsampled, such as the pages of an atlas,
+/// create a [`Downsampler`] once and call [`Downsampler::downsample()`] for every image.
+/// The images do not need to share the same format.
+///
+/// [`Downsampler`] is [`Send`] and [`Sync`]. Cloning it is cheap, as the clone shares the weights and only gets its own
+/// intermediate buffer, which allows every worker thread to use its own clone of a single plan. With `rayon`:
+///
+/// ```ignore
+/// let downsampled = textures
+///     .par_iter()
+///     .map_init(|| downsampler.clone(), |downsampler, texture| downsampler.downsample(texture))
+///     .collect::<Vec<_>>();
+/// ```
+pub struct Downsampler {
+    src_width: u32,
+    src_height: u32,
+    target_width: u32,
+    target_height: u32,
+    options: DownsampleOptions,
+    weights: Weights,
+    scratch_space: Vec<f32>,
+}
+
+impl Clone for Downsampler {
+    fn clone(&self) -> Self {
+        Self {
+            src_width: self.src_width,
+            src_height: self.src_height,
+            target_width: self.target_width,
+            target_height: self.target_height,
+            options: self.options,
+            weights: self.weights.clone(),
+            // The intermediate buffer is only reused between calls, its contents do not need to be copied.
+            scratch_space: Vec::new(),
+        }
+    }
+}
+
+impl Downsampler {
+    /// Precomputes the weights for downsampling images of `src_width` x `src_height` to `target_width` x `target_height`.
+    ///
+    /// Panics under the same conditions as [`downsample_with_options()`][crate::downsample_with_options()].
+    pub fn new(
+        src_width: u32,
+        src_height: u32,
+        target_width: u32,
+        target_height: u32,
+        options: &DownsampleOptions,
+    ) -> Self {
+        unwrap_or_panic(Self::try_new(
+            src_width,
+            src_height,
+            target_width,
+            target_height,
+            options,
+        ))
+    }
+
+    /// Version of [`new()`][Self::new()] which returns an error instead of panicking.
+    pub fn try_new(
+        src_width: u32,
+        src_height: u32,
+        target_width: u32,
+        target_height: u32,
+        options: &DownsampleOptions,
+    ) -> Result<Self, DownsampleError> {
+        let weights = precompute_weights(
+            src_width,
+            src_height,
+            target_width,
+            target_height,
+            &options.horizontal,
+            &options.vertical,
+        )?;
+
+        Ok(Self {
+            src_width,
+            src_height,
+            target_width,
+            target_height,
+            options: *options,
+            weights,
+            scratch_space: Vec::new(),
+        })
+    }
+
+    /// Precomputes the weights of the custom `filter` for downsampling images of `src_width` x `src_height`
+    /// to `target_width` x `target_height`.
+    ///
+    /// The [`horizontal`][DownsampleOptions::horizontal] and [`vertical`][DownsampleOptions::vertical] filters of `options`
+    /// are ignored, the other options are used as usual.
+    ///
+    /// Panics if [`try_new_with_filter()`][Self::try_new_with_filter()] would return an error.
+    pub fn new_with_filter(
+        src_width: u32,
+        src_height: u32,
+        target_width: u32,
+        target_height: u32,
+        filter: &dyn ResampleFilter,
+        options: &DownsampleOptions,
+    ) -> Self {
+        unwrap_or_panic(Self::try_new_with_filter(
+            src_width,
+            src_height,
+            target_width,
+            target_height,
+            filter,
+            options,
+        ))
+    }
+
+    /// Version of [`new_with_filter()`][Self::new_with_filter()] which returns an error instead of panicking
+    /// if the dimensions are invalid, or if the [support][ResampleFilter::support()] of `filter` is not a finite number larger than 0.
+    pub fn try_new_with_filter(
+        src_width: u32,
+        src_height: u32,
+        target_width: u32,
+        target_height: u32,
+        filter: &dyn ResampleFilter,
+        options: &DownsampleOptions,
+    ) -> Result<Self, DownsampleError> {
+        Self::try_new_with_filters(
+            src_width,
+            src_height,
+            target_width,
+            target_height,
+            filter,
+            filter,
+            options,
+        )
+    }
+
+    /// Version of [`new_with_filter()`][Self::new_with_filter()] which uses the custom `horizontal` filter along the width
+    /// and the custom `vertical` filter along the height.
+    ///
+    /// The weights are only shared between the axes if both filters are the same object.
+    ///
+    /// Panics if [`try_new_with_filters()`][Self::try_new_with_filters()] would return an error.
+    pub fn new_with_filters(
+        src_width: u32,
+        src_height: u32,
+        target_width: u32,
+        target_height: u32,
+        horizontal: &dyn ResampleFilter,
+        vertical: &dyn ResampleFilter,
+        options: &DownsampleOptions,
+    ) -> Self {
+        unwrap_or_panic(Self::try_new_with_filters(
+            src_width,
+            src_height,
+            target_width,
+            target_height,
+            horizontal,
+            vertical,
+            options,
+        ))
+    }
+
+    /// Version of [`new_with_filters()`][Self::new_with_filters()] which returns an error instead of panicking
+    /// under the same conditions as [`try_new_with_filter()`][Self::try_new_with_filter()].
+    pub fn try_new_with_filters(
+        src_width: u32,
+        src_height: u32,
+        target_width: u32,
+        target_height: u32,
+        horizontal: &dyn ResampleFilter,
+        vertical: &dyn ResampleFilter,
+        options: &DownsampleOptions,
+    ) -> Result<Self, DownsampleError> {
+        let weights = precompute_custom_weights(
+            src_width,
+            src_height,
+            target_width,
+            target_height,
+            horizontal,
+            vertical,
+        )?;
+
+        Ok(Self {
+            src_width,
+            src_height,
+            target_width,
+            target_height,
+            options: *options,
+            weights,
+            scratch_space: Vec::new(),
+        })
+    }
+
+    /// Returns the width and height of the images this [`Downsampler`] accepts.
+    pub fn src_dimensions(&self) -> (u32, u32) {
+        (self.src_width, self.src_height)
+    }
+
+    /// Returns the width and height of the images this [`Downsampler`] produces.
+    pub fn target_dimensions(&self) -> (u32, u32) {
+        (self.target_width, self.target_height)
+    }
+
+    /// Downsamples `src`, returning a `Vec` with the tightly packed pixels in the same format as `src`.
+    ///
+    /// Panics if the dimensions of `src` do not match [`src_dimensions()`][Self::src_dimensions()].
+    pub fn downsample(&mut self, src: &Image<'_, AlbedoFormat>) -> Vec<u8> {
+        unwrap_or_panic(self.try_downsample(src))
+    }
+
+    /// Version of [`downsample()`][Self::downsample()] which returns an error instead of panicking.
+    pub fn try_downsample(
+        &mut self,
+        src: &Image<'_, AlbedoFormat>,
+    ) -> Result<Vec<u8>, DownsampleError> {
+        // Multiplied in `usize`, as the number of pixels of large targets does not fit in `u32`.
+        let mut output = vec![
+            0u8;
+            self.target_width as usize
+                * self.target_height as usize
+                * src.format.pixel_size_in_bytes()
+        ];
+
+        self.try_downsample_into(
+            src,
+            &mut ImageMut::try_new(
+                &mut output,
+                self.target_width,
+                self.target_height,
+                src.format,
+            )?,
+        )?;
+
+        Ok(output)
+    }
+
+    /// Version of [`downsample()`][Self::downsample()] which writes to `dst` instead of returning a `Vec`.
+    ///
+    /// Panics if the dimensions of `src` and `dst` do not match [`src_dimensions()`][Self::src_dimensions()]
+    /// and [`target_dimensions()`][Self::target_dimensions()], or if their formats differ.
+    pub fn downsample_into(
+        &mut self,
+        src: &Image<'_, AlbedoFormat>,
+        dst: &mut ImageMut<'_, AlbedoFormat>,
+    ) {
+        unwrap_or_panic(self.try_downsample_into(src, dst))
+    }
+
+    /// Version of [`downsample_into()`][Self::downsample_into()] which returns an error instead of panicking.
+    pub fn try_downsample_into(
+        &mut self,
+        src: &Image<'_, AlbedoFormat>,
+        dst: &mut ImageMut<'_, AlbedoFormat>,
+    ) -> Result<(), DownsampleError> {
+        let check_dimensions = |(expected_width, expected_height): (u32, u32), width, height| {
+            if (width, height) == (expected_width, expected_height) {
+                Ok(())
+            } else {
+                Err(DownsampleError::DimensionMismatch {
+                    expected_width,
+                    expected_height,
+                    width,
+                    height,
+                })
+            }
+        };
+        check_dimensions(self.src_dimensions(), src.width, src.height)?;
+        check_dimensions(self.target_dimensions(), dst.width, dst.height)?;
+        if src.format != dst.format {
+            return Err(DownsampleError::FormatMismatch);
+        }
+
+        resample(
+            src,
+            &self.weights,
+            &self.options,
+            &mut self.scratch_space,
+            dst,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+
+/// Errors returned by the fallible `try_*` functions of this crate.
+///
+/// The non-`try_*` versions of these functions panic with the [`Display`][fmt::Display] representation of the error instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DownsampleError {
+    /// The width or height of an image is 0.
+    EmptyImage { width: u32, height: u32 },
+    /// The target has the same resolution as the source, so downsampling would not change the image.
+    SameResolution { width: u32, height: u32 },
+    /// The target is larger than the source along at least one axis.
+    Upsampling {
+        src_width: u32,
+        src_height: u32,
+        target_width: u32,
+        target_height: u32,
+    },
+    /// The filter scale is not a finite number larger than 0.
+    InvalidFilterScale(f32),
+    /// The number of lobes of [`Filter::Lanczos`][crate::Filter::Lanczos] is not a finite number larger than 0.
+    InvalidLanczosLobes(f32),
+    /// The support of a [`ResampleFilter`][crate::ResampleFilter] is not a finite number larger than 0.
+    InvalidFilterSupport(f32),
+    /// The filter is 0 at every source pixel in the footprint of a target pixel, or is not finite at one of them,
+    /// so its weights cannot be normalized. This happens when the support of the filter is too small for the
+    /// downsampling ratio.
+    DegenerateFilter,
+    /// The stride between pixels is smaller than the size of a pixel of the image's format.
+    PixelStrideTooSmall {
+        pixel_stride_in_bytes: usize,
+        pixel_size_in_bytes: usize,
+    },
+    /// The pitch between rows is smaller than the size of a row of pixels.
+    RowPitchTooSmall {
+        row_pitch_in_bytes: usize,
+        row_size_in_bytes: usize,
+    },
+    /// The pixel buffer is too small to hold all pixels described by the dimensions and layout of the image.
+    BufferTooSmall {
+        len_in_bytes: usize,
+        required_len_in_bytes: usize,
+    },
+    /// The operation needs an alpha channel, but the format does not have one.
+    NoAlphaChannel,
+    /// The requested channel is not one of the channels of the format.
+    ChannelOutOfRange { channel: usize, num_channels: usize },
+    /// The source and destination images do not have the same format.
+    FormatMismatch,
+    /// The dimensions of an image do not match the dimensions that were expected, such as those a
+    /// [`Downsampler`][crate::Downsampler] was created for.
+    DimensionMismatch {
+        expected_width: u32,
+        expected_height: u32,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl fmt::Display for DownsampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyImage { width, height } => write!(f, "Cannot downsample an image of {width}x{height} pixels, both dimensions must be at least 1."),
+            Self::SameResolution { width, height } => write!(f, "Trying to downsample to an image of the same resolution ({width}x{height}) as the source image. This operation can be avoided."),
+            Self::Upsampling {
+                src_width,
+                src_height,
+                target_width,
+                target_height,
+            } => write!(f, "The source image ({src_width}x{src_height}) is smaller than the target ({target_width}x{target_height}) along at least one axis. You are trying to upsample rather than downsample."),
+            Self::InvalidFilterScale(filter_scale) => write!(f, "filter_scale must be more than 0.0 when downsampling, but is {filter_scale}."),
+            Self::InvalidLanczosLobes(lobes) => write!(f, "The number of Lanczos lobes must be a finite number larger than 0.0, but is {lobes}."),
+            Self::InvalidFilterSupport(support) => write!(f, "The support of the filter must be a finite number larger than 0.0, but is {support}."),
+            Self::DegenerateFilter => write!(f, "The filter does not cover any source pixel in the footprint of a target pixel, so its weights cannot be normalized. Use a larger filter scale or support."),
+            Self::PixelStrideTooSmall {
+                pixel_stride_in_bytes,
+                pixel_size_in_bytes,
+            } => write!(f, "The stride between the pixels ({pixel_stride_in_bytes} bytes) cannot be lower than the size of a pixel according to the pixel format ({pixel_size_in_bytes} bytes)."),
+            Self::RowPitchTooSmall {
+                row_pitch_in_bytes,
+                row_size_in_bytes,
+            } => write!(f, "The pitch between the rows ({row_pitch_in_bytes} bytes) cannot be lower than the size of a row of pixels ({row_size_in_bytes} bytes)."),
+            Self::BufferTooSmall {
+                len_in_bytes,
+                required_len_in_bytes,
+            } => write!(f, "The pixel buffer is {len_in_bytes} bytes long, but the dimensions and layout of the image require at least {required_len_in_bytes} bytes."),
+            Self::NoAlphaChannel => write!(f, "Cannot retain alpha coverage on image with no alpha channel."),
+            Self::ChannelOutOfRange {
+                channel,
+                num_channels,
+            } => write!(f, "Channel {channel} does not exist in a format with {num_channels} channels."),
+            Self::FormatMismatch => write!(f, "The destination image must have the same format as the source image."),
+            Self::DimensionMismatch {
+                expected_width,
+                expected_height,
+                width,
+                height,
+            } => write!(f, "Expected an image of {expected_width}x{expected_height} pixels, but got {width}x{height}."),
+        }
+    }
+}
+
+impl std::error::Error for DownsampleError {}
+
+/// Unwraps the result of a `try_*` function for its panicking counterpart.
+#[track_caller]
+pub(crate) fn unwrap_or_panic<T>(result: Result<T, DownsampleError>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => panic!("{error}"),
+    }
+}
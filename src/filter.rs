@@ -0,0 +1,186 @@
+//! Filter kernels that the separable resampling passes can use.
+
+use std::f32::consts::PI;
+
+/// The kernel that is used to calculate the weights of the resampling passes.
+///
+/// All kernels are defined in units of target pixels, so their footprint in the source image grows with the downsampling ratio.
+/// The supports listed below are further multiplied by the [`filter_scale`][AxisFilter::filter_scale] of the axis.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Filter {
+    /// Averages the source pixels that fall inside of a target pixel. Support of 0.5.
+    Box,
+    /// Tent filter which linearly interpolates between the source pixels. Support of 1.
+    Triangle,
+    /// Mitchell-Netravali cubic with `B = C = 1/3`, which is soft with very little ringing. Support of 2.
+    Mitchell,
+    /// Catmull-Rom cubic, which is sharper than [`Filter::Mitchell`] with slight ringing. Support of 2.
+    CatmullRom,
+    /// Gaussian with a standard deviation of 0.5, which is blurry without any ringing. Support of 2.
+    Gaussian,
+    /// Sinc windowed by a Kaiser window with `alpha = 4`, which is sharp with little ringing. Support of 3.
+    Kaiser,
+    /// Sinc windowed by the central lobe of a wider sinc, `sinc(t) * sinc(t / lobes)`. Support of `lobes`.
+    ///
+    /// `lobes` is the `a` of the common Lanczos2, Lanczos3 and Lanczos4 filters. More lobes keep more detail and make the
+    /// filter sharper, at the cost of more ringing and more taps. Unlike [`filter_scale`][AxisFilter::filter_scale],
+    /// which stretches the whole kernel, it only changes the window. Must be a finite number larger than 0, and is
+    /// usually an integer.
+    Lanczos { lobes: f32 },
+}
+
+impl Filter {
+    /// Returns the radius of the kernel in target pixels, outside of which [`evaluate()`][Self::evaluate()] is 0.
+    pub(crate) fn support(self) -> f32 {
+        match self {
+            Self::Box => 0.5,
+            Self::Triangle => 1.0,
+            Self::Mitchell | Self::CatmullRom | Self::Gaussian => 2.0,
+            Self::Kaiser => 3.0,
+            Self::Lanczos { lobes } => lobes,
+        }
+    }
+
+    /// Evaluates the kernel at `t` target pixels from its center.
+    pub(crate) fn evaluate(self, t: f32) -> f32 {
+        let t = t.abs();
+        match self {
+            Self::Box => {
+                if t <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Triangle => (1.0 - t).max(0.0),
+            Self::Mitchell => cubic(t, 1.0 / 3.0, 1.0 / 3.0),
+            Self::CatmullRom => cubic(t, 0.0, 0.5),
+            Self::Gaussian => {
+                if t < 2.0 {
+                    (-2.0 * t * t).exp()
+                } else {
+                    0.0
+                }
+            }
+            Self::Kaiser => {
+                const WIDTH: f32 = 3.0;
+                const ALPHA: f32 = 4.0;
+                if t < WIDTH {
+                    let window = 1.0 - (t / WIDTH) * (t / WIDTH);
+                    sinc(t) * bessel_i0(ALPHA * window.sqrt()) / bessel_i0(ALPHA)
+                } else {
+                    0.0
+                }
+            }
+            Self::Lanczos { lobes } => {
+                if t < lobes {
+                    sinc(t) * sinc(t / lobes)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+fn sinc(x: f32) -> f32 {
+    // sin(x) / x is 0 / 0 at the center of the kernel, where its limit is 1.
+    if x.abs() < 1e-5 {
+        1.0
+    } else {
+        (x * PI).sin() / (x * PI)
+    }
+}
+
+/// The family of cubic filters from Mitchell and Netravali's "Reconstruction Filters in Computer Graphics".
+fn cubic(t: f32, b: f32, c: f32) -> f32 {
+    let (t2, t3) = (t * t, t * t * t);
+    let value = if t < 1.0 {
+        (12.0 - 9.0 * b - 6.0 * c) * t3 + (-18.0 + 12.0 * b + 6.0 * c) * t2 + (6.0 - 2.0 * b)
+    } else if t < 2.0 {
+        (-b - 6.0 * c) * t3
+            + (6.0 * b + 30.0 * c) * t2
+            + (-12.0 * b - 48.0 * c) * t
+            + (8.0 * b + 24.0 * c)
+    } else {
+        0.0
+    };
+    value / 6.0
+}
+
+/// Zeroth order modified Bessel function of the first kind, evaluated with its power series.
+fn bessel_i0(x: f32) -> f32 {
+    let half_x_squared = x * x / 4.0;
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    for k in 1..20 {
+        term *= half_x_squared / (k * k) as f32;
+        sum += term;
+        if term < sum * 1e-7 {
+            break;
+        }
+    }
+    sum
+}
+
+/// The filter settings for a single axis of the image, see [`DownsampleOptions`][crate::DownsampleOptions].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisFilter {
+    /// The kernel used to calculate the weights. Defaults to [`Filter::Lanczos`] with 3 lobes.
+    pub filter: Filter,
+    /// Stretches the kernel of [`filter`][Self::filter], multiplying its support and the number of source pixels
+    /// in the footprint of every target pixel. Values above 1.0 blur the result, values below 1.0 sharpen it and
+    /// are faster, but alias more. Must be a finite number larger than 0. Defaults to 1.0.
+    pub filter_scale: f32,
+}
+
+impl Default for AxisFilter {
+    fn default() -> Self {
+        Self {
+            filter: Filter::Lanczos { lobes: 3.0 },
+            filter_scale: 1.0,
+        }
+    }
+}
+
+impl From<Filter> for AxisFilter {
+    fn from(filter: Filter) -> Self {
+        Self {
+            filter,
+            ..Default::default()
+        }
+    }
+}
+
+/// A 1D kernel for the separable resampling passes, for filters that are not one of the built-in [`Filter`]s.
+///
+/// Like the built-in filters, the kernel is defined in units of target pixels.
+/// [`Downsampler::new_with_filter()`][crate::Downsampler::new_with_filter()] samples it at every source pixel in the
+/// footprint of a target pixel, and normalizes the samples such that their sum is 1, so the kernel itself does not need
+/// to be normalized. The weights are calculated once per distinct footprint and shared with the rest of the axis.
+///
+/// Only calculating the weights calls into the filter. The image itself is filtered by the same vectorized passes as
+/// with the built-in filters, so a custom filter is as fast as a built-in filter with the same support.
+/// Custom filters can also be used with [`downsample_with_filter()`][crate::downsample_with_filter()] and
+/// [`generate_mip_chain_with_filter()`][crate::generate_mip_chain_with_filter()].
+pub trait ResampleFilter {
+    /// Returns the radius of the kernel in target pixels, outside of which [`evaluate()`][Self::evaluate()] is 0.
+    /// Must be a finite number larger than 0.
+    fn support(&self) -> f32;
+
+    /// Evaluates the kernel at `t` target pixels from its center.
+    ///
+    /// The footprint is rounded up to whole source pixels, so this is also called slightly outside of the support,
+    /// where it should return 0. The samples in a footprint must not sum to 0.
+    fn evaluate(&self, t: f32) -> f32;
+}
+
+impl ResampleFilter for AxisFilter {
+    fn support(&self) -> f32 {
+        self.filter.support() * self.filter_scale
+    }
+
+    fn evaluate(&self, t: f32) -> f32 {
+        self.filter.evaluate(t / self.filter_scale)
+    }
+}
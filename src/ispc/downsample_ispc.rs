@@ -58,28 +58,46 @@ extern "C" {
     pub fn calculate_weights_lanczos(
         image_scale: f32,
         filter_scale: f32,
+        lobes: f32,
         dimensions: *const WeightDimensions,
         weights: *mut f32,
     );
 }
 extern "C" {
-    pub fn scale_to_alpha_coverage(
-        source_width: u32,
-        source_height: u32,
-        source_data: *const u8,
-        downsampled_width: u32,
-        downsampled_height: u32,
-        downsampled_image_data: *mut u8,
-        alpha_cutoff: *const f32,
+    pub fn calculate_weight_dimensions(
+        filter_scale: f32,
+        src: u32,
+        target: u32,
+        out_dimensions: *mut WeightDimensions,
     );
 }
 pub const NormalMapFormat_R8g8b8: NormalMapFormat = 0;
 pub const NormalMapFormat_R8g8TangentSpaceReconstructedZ: NormalMapFormat = 1;
+pub const NormalMapFormat_R16g16b16: NormalMapFormat = 2;
+pub const NormalMapFormat_R16g16TangentSpaceReconstructedZ: NormalMapFormat = 3;
+pub const NormalMapFormat_R8g8b8Snorm: NormalMapFormat = 4;
+pub const NormalMapFormat_R8g8SnormTangentSpaceReconstructedZ: NormalMapFormat = 5;
+pub const NormalMapFormat_R16g16b16Snorm: NormalMapFormat = 6;
+pub const NormalMapFormat_R16g16SnormTangentSpaceReconstructedZ: NormalMapFormat = 7;
+pub const NormalMapFormat_Octahedral8: NormalMapFormat = 8;
+pub const NormalMapFormat_Octahedral16: NormalMapFormat = 9;
 pub type NormalMapFormat = ::std::os::raw::c_uint;
 pub const PixelFormat_Rgba8Unorm: PixelFormat = 0;
 pub const PixelFormat_Rgb8Unorm: PixelFormat = 1;
 pub const PixelFormat_Rgba8Snorm: PixelFormat = 2;
 pub const PixelFormat_Rgb8Snorm: PixelFormat = 3;
+pub const PixelFormat_Srgba8: PixelFormat = 4;
+pub const PixelFormat_Srgb8: PixelFormat = 5;
+pub const PixelFormat_Rgba16Unorm: PixelFormat = 6;
+pub const PixelFormat_Rgb16Unorm: PixelFormat = 7;
+pub const PixelFormat_Rgba16Float: PixelFormat = 8;
+pub const PixelFormat_Rgba32Float: PixelFormat = 9;
+pub const PixelFormat_Rg8Unorm: PixelFormat = 10;
+pub const PixelFormat_R8Unorm: PixelFormat = 11;
+pub const PixelFormat_Rg16Unorm: PixelFormat = 12;
+pub const PixelFormat_R16Unorm: PixelFormat = 13;
+pub const PixelFormat_Rg8Snorm: PixelFormat = 14;
+pub const PixelFormat_R8Snorm: PixelFormat = 15;
 pub type PixelFormat = ::std::os::raw::c_uint;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -87,7 +105,8 @@ pub struct SourceImage {
     pub width: u32,
     pub height: u32,
     pub data: *const u8,
-    pub pixel_stride: u32,
+    pub pixel_stride: u64,
+    pub row_pitch: u64,
 }
 #[test]
 fn bindgen_test_layout_SourceImage() {
@@ -95,7 +114,7 @@ fn bindgen_test_layout_SourceImage() {
     let ptr = UNINIT.as_ptr();
     assert_eq!(
         ::std::mem::size_of::<SourceImage>(),
-        24usize,
+        32usize,
         concat!("Size of: ", stringify!(SourceImage))
     );
     assert_eq!(
@@ -143,6 +162,16 @@ fn bindgen_test_layout_SourceImage() {
             stringify!(pixel_stride)
         )
     );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).row_pitch) as usize - ptr as usize },
+        24usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(SourceImage),
+            "::",
+            stringify!(row_pitch)
+        )
+    );
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -150,7 +179,8 @@ pub struct DownsampledImage {
     pub width: u32,
     pub height: u32,
     pub data: *mut u8,
-    pub pixel_stride: u32,
+    pub pixel_stride: u64,
+    pub row_pitch: u64,
 }
 #[test]
 fn bindgen_test_layout_DownsampledImage() {
@@ -158,7 +188,7 @@ fn bindgen_test_layout_DownsampledImage() {
     let ptr = UNINIT.as_ptr();
     assert_eq!(
         ::std::mem::size_of::<DownsampledImage>(),
-        24usize,
+        32usize,
         concat!("Size of: ", stringify!(DownsampledImage))
     );
     assert_eq!(
@@ -206,6 +236,109 @@ fn bindgen_test_layout_DownsampledImage() {
             stringify!(pixel_stride)
         )
     );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).row_pitch) as usize - ptr as usize },
+        24usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(DownsampledImage),
+            "::",
+            stringify!(row_pitch)
+        )
+    );
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct CachedWeight {
+    pub start: u32,
+    pub offset: u32,
+    pub count: u32,
+}
+#[test]
+fn bindgen_test_layout_CachedWeight() {
+    const UNINIT: ::std::mem::MaybeUninit<CachedWeight> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<CachedWeight>(),
+        12usize,
+        concat!("Size of: ", stringify!(CachedWeight))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<CachedWeight>(),
+        4usize,
+        concat!("Alignment of ", stringify!(CachedWeight))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).start) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(CachedWeight),
+            "::",
+            stringify!(start)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).offset) as usize - ptr as usize },
+        4usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(CachedWeight),
+            "::",
+            stringify!(offset)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).count) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(CachedWeight),
+            "::",
+            stringify!(count)
+        )
+    );
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct WeightCollection {
+    pub lines: *const CachedWeight,
+    pub coefficients: *const f32,
+}
+#[test]
+fn bindgen_test_layout_WeightCollection() {
+    const UNINIT: ::std::mem::MaybeUninit<WeightCollection> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<WeightCollection>(),
+        16usize,
+        concat!("Size of: ", stringify!(WeightCollection))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<WeightCollection>(),
+        8usize,
+        concat!("Alignment of ", stringify!(WeightCollection))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).lines) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(WeightCollection),
+            "::",
+            stringify!(lines)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).coefficients) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(WeightCollection),
+            "::",
+            stringify!(coefficients)
+        )
+    );
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -252,7 +385,9 @@ fn bindgen_test_layout_SampleWeights() {
 #[derive(Debug, Copy, Clone)]
 pub struct DownsamplingContext {
     pub weights: SampleWeights,
-    pub scratch_space: *mut u8,
+    pub scratch_space: *mut f32,
+    pub clamp_to_footprint: bool,
+    pub suppress_hdr_ringing: bool,
 }
 #[test]
 fn bindgen_test_layout_DownsamplingContext() {
@@ -260,7 +395,7 @@ fn bindgen_test_layout_DownsamplingContext() {
     let ptr = UNINIT.as_ptr();
     assert_eq!(
         ::std::mem::size_of::<DownsamplingContext>(),
-        24usize,
+        32usize,
         concat!("Size of: ", stringify!(DownsamplingContext))
     );
     assert_eq!(
@@ -288,64 +423,41 @@ fn bindgen_test_layout_DownsamplingContext() {
             stringify!(scratch_space)
         )
     );
-}
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct WeightCollection {
-    pub starts: *const u32,
-    pub weight_counts: *const u32,
-    pub values: *const *const f32,
-}
-#[test]
-fn bindgen_test_layout_WeightCollection() {
-    const UNINIT: ::std::mem::MaybeUninit<WeightCollection> = ::std::mem::MaybeUninit::uninit();
-    let ptr = UNINIT.as_ptr();
     assert_eq!(
-        ::std::mem::size_of::<WeightCollection>(),
+        unsafe { ::std::ptr::addr_of!((*ptr).clamp_to_footprint) as usize - ptr as usize },
         24usize,
-        concat!("Size of: ", stringify!(WeightCollection))
-    );
-    assert_eq!(
-        ::std::mem::align_of::<WeightCollection>(),
-        8usize,
-        concat!("Alignment of ", stringify!(WeightCollection))
-    );
-    assert_eq!(
-        unsafe { ::std::ptr::addr_of!((*ptr).starts) as usize - ptr as usize },
-        0usize,
         concat!(
             "Offset of field: ",
-            stringify!(WeightCollection),
+            stringify!(DownsamplingContext),
             "::",
-            stringify!(starts)
+            stringify!(clamp_to_footprint)
         )
     );
     assert_eq!(
-        unsafe { ::std::ptr::addr_of!((*ptr).weight_counts) as usize - ptr as usize },
-        8usize,
+        unsafe { ::std::ptr::addr_of!((*ptr).suppress_hdr_ringing) as usize - ptr as usize },
+        25usize,
         concat!(
             "Offset of field: ",
-            stringify!(WeightCollection),
+            stringify!(DownsamplingContext),
             "::",
-            stringify!(weight_counts)
+            stringify!(suppress_hdr_ringing)
         )
     );
-    assert_eq!(
-        unsafe { ::std::ptr::addr_of!((*ptr).values) as usize - ptr as usize },
-        16usize,
-        concat!(
-            "Offset of field: ",
-            stringify!(WeightCollection),
-            "::",
-            stringify!(values)
-        )
+}
+extern "C" {
+    pub fn resample_with_cached_weights_1(
+        src: *const SourceImage,
+        dst: *mut DownsampledImage,
+        pixel_format: PixelFormat,
+        ctx: *mut DownsamplingContext,
     );
 }
 extern "C" {
-    pub fn downsample_normal_map(
+    pub fn resample_with_cached_weights_2(
         src: *const SourceImage,
         dst: *mut DownsampledImage,
-        normal_map_format: NormalMapFormat,
+        pixel_format: PixelFormat,
+        ctx: *mut DownsamplingContext,
     );
 }
 extern "C" {
@@ -365,11 +477,60 @@ extern "C" {
     );
 }
 extern "C" {
-    pub fn calculate_weight_dimensions(
-        filter_scale: f32,
-        src: u32,
-        target: u32,
-        out_dimensions: *mut WeightDimensions,
+    pub fn resample_normal_map_with_cached_weights(
+        src: *const SourceImage,
+        dst: *mut DownsampledImage,
+        normal_map_format: NormalMapFormat,
+        ctx: *mut DownsamplingContext,
+    );
+}
+extern "C" {
+    pub fn downsample_normal_map(
+        src: *const SourceImage,
+        dst: *mut DownsampledImage,
+        normal_map_format: NormalMapFormat,
+    );
+}
+extern "C" {
+    pub fn decode_normal_map(
+        src: *const SourceImage,
+        normal_map_format: NormalMapFormat,
+        normals: *mut f32,
+    );
+}
+extern "C" {
+    pub fn average_normals(
+        src_width: u32,
+        src_height: u32,
+        src: *const f32,
+        dst_width: u32,
+        dst_height: u32,
+        dst: *mut f32,
+    );
+}
+extern "C" {
+    pub fn write_normals(
+        normals: *const f32,
+        dst: *mut DownsampledImage,
+        normal_map_format: NormalMapFormat,
+        variance: *mut f32,
+    );
+}
+extern "C" {
+    pub fn calculate_alpha_coverage(
+        image: *const SourceImage,
+        pixel_format: PixelFormat,
+        alpha_channel: u32,
+        alpha_cutoff: *const f32,
+    ) -> f32;
+}
+extern "C" {
+    pub fn scale_to_alpha_coverage(
+        coverage: f32,
+        downsampled: *mut DownsampledImage,
+        pixel_format: PixelFormat,
+        alpha_channel: u32,
+        alpha_cutoff: *const f32,
     );
 }
 }
\ No newline at end of file
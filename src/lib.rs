@@ -1,12 +1,27 @@
-use std::{collections::HashMap, rc::Rc};
+pub use downsampler::Downsampler;
+use error::unwrap_or_panic;
+pub use error::DownsampleError;
+pub use filter::{AxisFilter, Filter, ResampleFilter};
+pub use mip_chain::{
+    generate_mip_chain, generate_mip_chain_with_filter, generate_normal_map_mip_chain,
+    try_generate_mip_chain, try_generate_mip_chain_with_filter, MipChain, MipLevel, MipOptions,
+    MipSource, NormalMapMipOptions,
+};
+use std::sync::Arc;
 
-use ispc::WeightCollection;
+use weights::{calculate_axis_weights, calculate_weights, WeightCollection, Weights};
 
+mod downsampler;
+mod error;
+mod filter;
 mod ispc;
+mod mip_chain;
+mod resample;
+mod weights;
 
 pub trait ImagePixelFormat: Copy {
     /// Returns the number of channels that an image of this format would have in memory.
-    /// For example, while a normal map of format [`NormalMapFormat::R8g8TangentSpaceReconstructedZ`] would still have 3 channels when sampled,
+    /// For example, while a normal map of format [`NormalMapFormat::Rg8TangentSpaceReconstructedZ`] would still have 3 channels when sampled,
     /// in memory it will have 2 channels.
     fn num_channel_in_memory(self) -> usize;
 
@@ -20,66 +35,206 @@ pub trait ImagePixelFormat: Copy {
     }
 }
 
+/// Formats that can be downsampled with [`downsample()`].
+///
+/// Channels of 16-bit formats are stored as little-endian.
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
 pub enum AlbedoFormat {
+    /// Single channel, such as roughness, metalness or ambient occlusion masks.
+    R8Unorm,
+    /// Single channel stored as two's complement, decoded to [-1, 1] before filtering.
+    R8Snorm,
+    Rg8Unorm,
+    /// Two channels stored as two's complement, decoded to [-1, 1] before filtering.
+    Rg8Snorm,
     Rgb8Unorm,
+    /// 8-bit RGB stored as two's complement, decoded to [-1, 1] before filtering.
     Rgb8Snorm,
+    /// 8-bit RGB encoded with the sRGB transfer function.
+    /// The color is converted to linear before filtering and encoded back to sRGB when written.
     Srgb8,
     Rgba8Unorm,
+    /// 8-bit RGBA stored as two's complement, decoded to [-1, 1] before filtering.
     Rgba8Snorm,
+    /// 8-bit RGBA where the color channels are encoded with the sRGB transfer function.
+    /// Alpha is stored linearly and is filtered as is.
     Srgba8,
+    R16Unorm,
+    Rg16Unorm,
+    Rgb16Unorm,
+    Rgba16Unorm,
+    /// Half precision float RGBA. Values are not clamped, so this can be used for HDR data.
+    Rgba16Float,
+    /// Single precision float RGBA. Values are not clamped, so this can be used for HDR data.
+    Rgba32Float,
+}
+
+impl AlbedoFormat {
+    /// Returns whether the color channels of this format are encoded with the sRGB transfer function.
+    pub fn is_srgb(self) -> bool {
+        matches!(self, Self::Srgb8 | Self::Srgba8)
+    }
+
+    /// Returns whether the channels of this format are stored as signed normalized values.
+    pub fn is_snorm(self) -> bool {
+        matches!(
+            self,
+            Self::R8Snorm | Self::Rg8Snorm | Self::Rgb8Snorm | Self::Rgba8Snorm
+        )
+    }
+
+    /// Returns whether the channels of this format are stored as floats, which can hold values outside of [0, 1].
+    pub fn is_float(self) -> bool {
+        matches!(self, Self::Rgba16Float | Self::Rgba32Float)
+    }
+
+    /// Returns the channel which [`scale_alpha_to_original_coverage()`] treats as alpha.
+    ///
+    /// This is the last channel of formats with four channels, and the only channel of single channel formats,
+    /// which are treated as alpha masks. Other formats have no alpha channel.
+    pub fn alpha_channel(self) -> Option<usize> {
+        match self.num_channel_in_memory() {
+            4 => Some(3),
+            1 => Some(0),
+            _ => None,
+        }
+    }
 }
 
 impl ImagePixelFormat for AlbedoFormat {
     fn num_channel_in_memory(self) -> usize {
         match self {
-            Self::Rgb8Unorm | Self::Rgb8Snorm | Self::Srgb8 => 3,
-            Self::Rgba8Unorm | Self::Rgba8Snorm | Self::Srgba8 => 4,
+            Self::R8Unorm | Self::R8Snorm | Self::R16Unorm => 1,
+            Self::Rg8Unorm | Self::Rg8Snorm | Self::Rg16Unorm => 2,
+            Self::Rgb8Unorm | Self::Rgb8Snorm | Self::Srgb8 | Self::Rgb16Unorm => 3,
+            Self::Rgba8Unorm
+            | Self::Rgba8Snorm
+            | Self::Srgba8
+            | Self::Rgba16Unorm
+            | Self::Rgba16Float
+            | Self::Rgba32Float => 4,
         }
     }
 
     fn channel_size_in_bytes(self) -> usize {
         match self {
-            AlbedoFormat::Rgb8Unorm
-            | AlbedoFormat::Rgb8Snorm
-            | AlbedoFormat::Srgb8
-            | AlbedoFormat::Rgba8Unorm
-            | AlbedoFormat::Rgba8Snorm
-            | AlbedoFormat::Srgba8 => 1,
+            Self::R8Unorm
+            | Self::R8Snorm
+            | Self::Rg8Unorm
+            | Self::Rg8Snorm
+            | Self::Rgb8Unorm
+            | Self::Rgb8Snorm
+            | Self::Srgb8
+            | Self::Rgba8Unorm
+            | Self::Rgba8Snorm
+            | Self::Srgba8 => 1,
+            Self::R16Unorm
+            | Self::Rg16Unorm
+            | Self::Rgb16Unorm
+            | Self::Rgba16Unorm
+            | Self::Rgba16Float => 2,
+            Self::Rgba32Float => 4,
         }
     }
 }
 
-impl From<AlbedoFormat> for ispc::downsample_ispc::PixelFormat {
+impl From<AlbedoFormat> for ispc::PixelFormat {
     fn from(value: AlbedoFormat) -> Self {
         match value {
+            AlbedoFormat::R8Unorm => ispc::PixelFormat_R8Unorm,
+            AlbedoFormat::R8Snorm => ispc::PixelFormat_R8Snorm,
+            AlbedoFormat::Rg8Unorm => ispc::PixelFormat_Rg8Unorm,
+            AlbedoFormat::Rg8Snorm => ispc::PixelFormat_Rg8Snorm,
             AlbedoFormat::Rgb8Unorm => ispc::PixelFormat_Rgb8Unorm,
             AlbedoFormat::Rgb8Snorm => ispc::PixelFormat_Rgb8Snorm,
-            AlbedoFormat::Srgb8 => ispc::PixelFormat_Rgb8Unorm,
+            AlbedoFormat::Srgb8 => ispc::PixelFormat_Srgb8,
             AlbedoFormat::Rgba8Unorm => ispc::PixelFormat_Rgba8Unorm,
             AlbedoFormat::Rgba8Snorm => ispc::PixelFormat_Rgba8Snorm,
-            AlbedoFormat::Srgba8 => ispc::PixelFormat_Rgba8Unorm,
+            AlbedoFormat::Srgba8 => ispc::PixelFormat_Srgba8,
+            AlbedoFormat::R16Unorm => ispc::PixelFormat_R16Unorm,
+            AlbedoFormat::Rg16Unorm => ispc::PixelFormat_Rg16Unorm,
+            AlbedoFormat::Rgb16Unorm => ispc::PixelFormat_Rgb16Unorm,
+            AlbedoFormat::Rgba16Unorm => ispc::PixelFormat_Rgba16Unorm,
+            AlbedoFormat::Rgba16Float => ispc::PixelFormat_Rgba16Float,
+            AlbedoFormat::Rgba32Float => ispc::PixelFormat_Rgba32Float,
         }
     }
 }
 
+/// Formats that can be downsampled with [`downsample_normal_map()`].
+///
+/// The channels of unorm formats are stored as unsigned normalized values, which are mapped to [-1, 1] with `value * 2 - 1`.
+/// The channels of snorm formats are stored as two's complement and map to [-1, 1] directly.
+/// Channels of 16-bit formats are stored as little-endian.
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
 pub enum NormalMapFormat {
     Rgb8,
     Rg8TangentSpaceReconstructedZ,
+    Rgb16,
+    Rg16TangentSpaceReconstructedZ,
+    Rgb8Snorm,
+    /// Two 8-bit snorm channels with a reconstructed Z, as stored in BC5 snorm textures.
+    Rg8SnormTangentSpaceReconstructedZ,
+    Rgb16Snorm,
+    Rg16SnormTangentSpaceReconstructedZ,
+    /// Two 8-bit unorm channels holding the normal in octahedral encoding.
+    /// The normals are decoded to 3D vectors before filtering and encoded again afterwards.
+    Octahedral8,
+    /// Two 16-bit unorm channels holding the normal in octahedral encoding.
+    /// The normals are decoded to 3D vectors before filtering and encoded again afterwards.
+    Octahedral16,
+}
+
+impl NormalMapFormat {
+    /// Returns whether the Z component of the normal is not stored, but reconstructed from X and Y.
+    pub fn reconstructs_z(self) -> bool {
+        matches!(
+            self,
+            Self::Rg8TangentSpaceReconstructedZ
+                | Self::Rg16TangentSpaceReconstructedZ
+                | Self::Rg8SnormTangentSpaceReconstructedZ
+                | Self::Rg16SnormTangentSpaceReconstructedZ
+        )
+    }
+
+    /// Returns whether the normal is stored in two channels with an octahedral encoding.
+    pub fn is_octahedral(self) -> bool {
+        matches!(self, Self::Octahedral8 | Self::Octahedral16)
+    }
+
+    /// Returns whether the channels of this format are stored as signed normalized values.
+    pub fn is_snorm(self) -> bool {
+        matches!(
+            self,
+            Self::Rgb8Snorm
+                | Self::Rg8SnormTangentSpaceReconstructedZ
+                | Self::Rgb16Snorm
+                | Self::Rg16SnormTangentSpaceReconstructedZ
+        )
+    }
 }
 
 impl ImagePixelFormat for NormalMapFormat {
     fn num_channel_in_memory(self) -> usize {
-        match self {
-            NormalMapFormat::Rgb8 => 3,
-            NormalMapFormat::Rg8TangentSpaceReconstructedZ => 2,
+        if self.reconstructs_z() || self.is_octahedral() {
+            2
+        } else {
+            3
         }
     }
 
     fn channel_size_in_bytes(self) -> usize {
         match self {
-            Self::Rgb8 | Self::Rg8TangentSpaceReconstructedZ => 1,
+            Self::Rgb8
+            | Self::Rg8TangentSpaceReconstructedZ
+            | Self::Rgb8Snorm
+            | Self::Rg8SnormTangentSpaceReconstructedZ
+            | Self::Octahedral8 => 1,
+            Self::Rgb16
+            | Self::Rg16TangentSpaceReconstructedZ
+            | Self::Rgb16Snorm
+            | Self::Rg16SnormTangentSpaceReconstructedZ
+            | Self::Octahedral16 => 2,
         }
     }
 }
@@ -91,27 +246,122 @@ impl From<NormalMapFormat> for ispc::NormalMapFormat {
             NormalMapFormat::Rg8TangentSpaceReconstructedZ => {
                 ispc::NormalMapFormat_R8g8TangentSpaceReconstructedZ
             }
+            NormalMapFormat::Rgb16 => ispc::NormalMapFormat_R16g16b16,
+            NormalMapFormat::Rg16TangentSpaceReconstructedZ => {
+                ispc::NormalMapFormat_R16g16TangentSpaceReconstructedZ
+            }
+            NormalMapFormat::Rgb8Snorm => ispc::NormalMapFormat_R8g8b8Snorm,
+            NormalMapFormat::Rg8SnormTangentSpaceReconstructedZ => {
+                ispc::NormalMapFormat_R8g8SnormTangentSpaceReconstructedZ
+            }
+            NormalMapFormat::Rgb16Snorm => ispc::NormalMapFormat_R16g16b16Snorm,
+            NormalMapFormat::Rg16SnormTangentSpaceReconstructedZ => {
+                ispc::NormalMapFormat_R16g16SnormTangentSpaceReconstructedZ
+            }
+            NormalMapFormat::Octahedral8 => ispc::NormalMapFormat_Octahedral8,
+            NormalMapFormat::Octahedral16 => ispc::NormalMapFormat_Octahedral16,
         }
     }
 }
 
+/// Checks that a buffer of `len_in_bytes` bytes can hold an image with the given dimensions and layout.
+fn validate_layout(
+    len_in_bytes: usize,
+    width: u32,
+    height: u32,
+    pixel_size_in_bytes: usize,
+    pixel_stride_in_bytes: usize,
+    row_pitch_in_bytes: usize,
+) -> Result<(), DownsampleError> {
+    if width == 0 || height == 0 {
+        return Err(DownsampleError::EmptyImage { width, height });
+    }
+    if pixel_stride_in_bytes < pixel_size_in_bytes {
+        return Err(DownsampleError::PixelStrideTooSmall {
+            pixel_stride_in_bytes,
+            pixel_size_in_bytes,
+        });
+    }
+
+    // The last pixel of a row only has to hold the pixel itself, not the full stride.
+    let row_size_in_bytes = (width as usize - 1)
+        .saturating_mul(pixel_stride_in_bytes)
+        .saturating_add(pixel_size_in_bytes);
+    if row_pitch_in_bytes < row_size_in_bytes {
+        return Err(DownsampleError::RowPitchTooSmall {
+            row_pitch_in_bytes,
+            row_size_in_bytes,
+        });
+    }
+
+    let required_len_in_bytes = (height as usize - 1)
+        .saturating_mul(row_pitch_in_bytes)
+        .saturating_add(row_size_in_bytes);
+    if len_in_bytes < required_len_in_bytes {
+        return Err(DownsampleError::BufferTooSmall {
+            len_in_bytes,
+            required_len_in_bytes,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the pitch of rows with `width` pixels that are `pixel_stride_in_bytes` apart.
+///
+/// A pitch that does not fit in `usize` cannot fit in a buffer of `len_in_bytes` bytes either, so it is reported as such.
+fn packed_row_pitch(
+    len_in_bytes: usize,
+    width: u32,
+    pixel_stride_in_bytes: usize,
+) -> Result<usize, DownsampleError> {
+    (width as usize)
+        .checked_mul(pixel_stride_in_bytes)
+        .ok_or(DownsampleError::BufferTooSmall {
+            len_in_bytes,
+            required_len_in_bytes: usize::MAX,
+        })
+}
+
 /// Describes a source image which can be used for [`downsample()`]
 /// The pixel data is stored as a slice to avoid unnecessarily cloning it.
+///
+/// The pixels can have a stride and the rows can have a pitch, so that padded images such as mapped GPU readback buffers
+/// can be read without repacking them.
+/// The pixel data is validated when the image is created, so every pixel described by the dimensions and layout
+/// is guaranteed to be inside of the slice.
 pub struct Image<'a, F: ImagePixelFormat> {
     pixels: &'a [u8],
     width: u32,
     height: u32,
     pixel_stride_in_bytes: usize,
+    row_pitch_in_bytes: usize,
     format: F,
 }
 
 impl<'a, F: ImagePixelFormat> Image<'a, F> {
     /// Creates a new source image from the given pixel data slice, dimensions and format.
+    ///
+    /// Panics if [`try_new()`][Self::try_new()] would return an error.
     pub fn new(pixels: &'a [u8], width: u32, height: u32, format: F) -> Self {
+        unwrap_or_panic(Self::try_new(pixels, width, height, format))
+    }
+
+    /// Version of [`new()`][Self::new()] which returns an error if either dimension is 0
+    /// or if `pixels` is too short to hold `width * height` pixels of `format`.
+    pub fn try_new(
+        pixels: &'a [u8],
+        width: u32,
+        height: u32,
+        format: F,
+    ) -> Result<Self, DownsampleError> {
         let pixel_size = format.pixel_size_in_bytes();
-        Self::new_with_pixel_stride(pixels, width, height, format, pixel_size)
+        Self::try_new_with_pixel_stride(pixels, width, height, format, pixel_size)
     }
 
+    /// Creates a new source image with tightly packed rows, where every pixel starts `pixel_stride_in_bytes` after the previous one.
+    ///
+    /// Panics if [`try_new_with_pixel_stride()`][Self::try_new_with_pixel_stride()] would return an error.
     pub fn new_with_pixel_stride(
         pixels: &'a [u8],
         width: u32,
@@ -119,12 +369,243 @@ impl<'a, F: ImagePixelFormat> Image<'a, F> {
         format: F,
         pixel_stride_in_bytes: usize,
     ) -> Self {
-        Self {
+        unwrap_or_panic(Self::try_new_with_pixel_stride(
+            pixels,
+            width,
+            height,
+            format,
+            pixel_stride_in_bytes,
+        ))
+    }
+
+    /// Version of [`new_with_pixel_stride()`][Self::new_with_pixel_stride()] which returns an error if either dimension is 0,
+    /// if `pixel_stride_in_bytes` is smaller than a pixel of `format` or if `pixels` is too short to hold all pixels.
+    ///
+    /// The last pixel only has to fit its own size rather than the full stride.
+    pub fn try_new_with_pixel_stride(
+        pixels: &'a [u8],
+        width: u32,
+        height: u32,
+        format: F,
+        pixel_stride_in_bytes: usize,
+    ) -> Result<Self, DownsampleError> {
+        let row_pitch_in_bytes = packed_row_pitch(pixels.len(), width, pixel_stride_in_bytes)?;
+        Self::try_new_with_row_pitch(
+            pixels,
+            width,
+            height,
+            format,
+            pixel_stride_in_bytes,
+            row_pitch_in_bytes,
+        )
+    }
+
+    /// Creates a new source image where every pixel starts `pixel_stride_in_bytes` after the previous one,
+    /// and every row starts `row_pitch_in_bytes` after the previous one.
+    ///
+    /// Panics if [`try_new_with_row_pitch()`][Self::try_new_with_row_pitch()] would return an error.
+    pub fn new_with_row_pitch(
+        pixels: &'a [u8],
+        width: u32,
+        height: u32,
+        format: F,
+        pixel_stride_in_bytes: usize,
+        row_pitch_in_bytes: usize,
+    ) -> Self {
+        unwrap_or_panic(Self::try_new_with_row_pitch(
+            pixels,
+            width,
+            height,
+            format,
+            pixel_stride_in_bytes,
+            row_pitch_in_bytes,
+        ))
+    }
+
+    /// Version of [`new_with_row_pitch()`][Self::new_with_row_pitch()] which returns an error if either dimension is 0,
+    /// if the stride or pitch are too small to fit a pixel or row, or if `pixels` is too short to hold all pixels.
+    ///
+    /// The last row only has to fit its pixels rather than the full pitch.
+    pub fn try_new_with_row_pitch(
+        pixels: &'a [u8],
+        width: u32,
+        height: u32,
+        format: F,
+        pixel_stride_in_bytes: usize,
+        row_pitch_in_bytes: usize,
+    ) -> Result<Self, DownsampleError> {
+        validate_layout(
+            pixels.len(),
+            width,
+            height,
+            format.pixel_size_in_bytes(),
+            pixel_stride_in_bytes,
+            row_pitch_in_bytes,
+        )?;
+
+        Ok(Self {
             pixels,
             width,
             height,
             pixel_stride_in_bytes,
+            row_pitch_in_bytes,
             format,
+        })
+    }
+
+    /// Returns the byte offset of the pixel at `x`, `y`.
+    pub(crate) fn pixel_offset(&self, x: usize, y: usize) -> usize {
+        y * self.row_pitch_in_bytes + x * self.pixel_stride_in_bytes
+    }
+
+    /// Returns the representation of this image which is passed to the ISPC kernels.
+    /// It points into the pixels of this image, so it must not outlive it.
+    pub(crate) fn ispc_representation(&self) -> ispc::SourceImage {
+        ispc::SourceImage {
+            width: self.width,
+            height: self.height,
+            data: self.pixels.as_ptr(),
+            pixel_stride: self.pixel_stride_in_bytes as u64,
+            row_pitch: self.row_pitch_in_bytes as u64,
+        }
+    }
+}
+
+/// Describes a destination image which functions such as [`downsample_into()`] write to.
+///
+/// Unlike the tightly packed `Vec`s returned by [`downsample()`], the pixels can have a stride and the rows can have a pitch,
+/// which allows writing directly into a mapped staging buffer or into a part of a larger allocation.
+/// Bytes which are not part of a pixel as described by the format are left untouched.
+///
+/// Like [`Image`], the layout is validated against the length of the slice when the image is created.
+pub struct ImageMut<'a, F: ImagePixelFormat> {
+    pixels: &'a mut [u8],
+    width: u32,
+    height: u32,
+    pixel_stride_in_bytes: usize,
+    row_pitch_in_bytes: usize,
+    format: F,
+}
+
+impl<'a, F: ImagePixelFormat> ImageMut<'a, F> {
+    /// Creates a new destination image with tightly packed pixels and rows.
+    ///
+    /// Panics if [`try_new()`][Self::try_new()] would return an error.
+    pub fn new(pixels: &'a mut [u8], width: u32, height: u32, format: F) -> Self {
+        unwrap_or_panic(Self::try_new(pixels, width, height, format))
+    }
+
+    /// Version of [`new()`][Self::new()] which returns an error instead of panicking.
+    pub fn try_new(
+        pixels: &'a mut [u8],
+        width: u32,
+        height: u32,
+        format: F,
+    ) -> Result<Self, DownsampleError> {
+        let pixel_size = format.pixel_size_in_bytes();
+        Self::try_new_with_pixel_stride(pixels, width, height, format, pixel_size)
+    }
+
+    /// Creates a new destination image with tightly packed rows, where every pixel starts `pixel_stride_in_bytes` after the previous one.
+    ///
+    /// Panics if [`try_new_with_pixel_stride()`][Self::try_new_with_pixel_stride()] would return an error.
+    pub fn new_with_pixel_stride(
+        pixels: &'a mut [u8],
+        width: u32,
+        height: u32,
+        format: F,
+        pixel_stride_in_bytes: usize,
+    ) -> Self {
+        unwrap_or_panic(Self::try_new_with_pixel_stride(
+            pixels,
+            width,
+            height,
+            format,
+            pixel_stride_in_bytes,
+        ))
+    }
+
+    /// Version of [`new_with_pixel_stride()`][Self::new_with_pixel_stride()] which returns an error instead of panicking.
+    pub fn try_new_with_pixel_stride(
+        pixels: &'a mut [u8],
+        width: u32,
+        height: u32,
+        format: F,
+        pixel_stride_in_bytes: usize,
+    ) -> Result<Self, DownsampleError> {
+        let row_pitch_in_bytes = packed_row_pitch(pixels.len(), width, pixel_stride_in_bytes)?;
+        Self::try_new_with_row_pitch(
+            pixels,
+            width,
+            height,
+            format,
+            pixel_stride_in_bytes,
+            row_pitch_in_bytes,
+        )
+    }
+
+    /// Creates a new destination image where every pixel starts `pixel_stride_in_bytes` after the previous one,
+    /// and every row starts `row_pitch_in_bytes` after the previous one.
+    ///
+    /// Panics if [`try_new_with_row_pitch()`][Self::try_new_with_row_pitch()] would return an error.
+    pub fn new_with_row_pitch(
+        pixels: &'a mut [u8],
+        width: u32,
+        height: u32,
+        format: F,
+        pixel_stride_in_bytes: usize,
+        row_pitch_in_bytes: usize,
+    ) -> Self {
+        unwrap_or_panic(Self::try_new_with_row_pitch(
+            pixels,
+            width,
+            height,
+            format,
+            pixel_stride_in_bytes,
+            row_pitch_in_bytes,
+        ))
+    }
+
+    /// Version of [`new_with_row_pitch()`][Self::new_with_row_pitch()] which returns an error if either dimension is 0,
+    /// if the stride or pitch are too small to fit a pixel or row, or if `pixels` is too short to hold all pixels.
+    ///
+    /// The last row only has to fit its pixels rather than the full pitch.
+    pub fn try_new_with_row_pitch(
+        pixels: &'a mut [u8],
+        width: u32,
+        height: u32,
+        format: F,
+        pixel_stride_in_bytes: usize,
+        row_pitch_in_bytes: usize,
+    ) -> Result<Self, DownsampleError> {
+        validate_layout(
+            pixels.len(),
+            width,
+            height,
+            format.pixel_size_in_bytes(),
+            pixel_stride_in_bytes,
+            row_pitch_in_bytes,
+        )?;
+
+        Ok(Self {
+            pixels,
+            width,
+            height,
+            pixel_stride_in_bytes,
+            row_pitch_in_bytes,
+            format,
+        })
+    }
+
+    /// Returns the representation of this image which is passed to the ISPC kernels.
+    /// It points into the pixels of this image, so it must not outlive it.
+    pub(crate) fn ispc_representation(&mut self) -> ispc::DownsampledImage {
+        ispc::DownsampledImage {
+            width: self.width,
+            height: self.height,
+            data: self.pixels.as_mut_ptr(),
+            pixel_stride: self.pixel_stride_in_bytes as u64,
+            row_pitch: self.row_pitch_in_bytes as u64,
         }
     }
 }
@@ -135,149 +616,369 @@ impl<'a, F: ImagePixelFormat> Image<'a, F> {
 /// which the percentage of visible texels will be. Otherwise, visibility is considered
 /// a linear sum of the alpha values instead and the source and target alpha coverage
 /// are calculated the same way.
+///
+/// Alpha is read from [`AlbedoFormat::alpha_channel()`], so single channel formats are treated as alpha masks.
+/// To use a different channel, see [`scale_alpha_to_original_coverage_in_channel()`].
+/// For signed normalized formats alpha is decoded to [-1, 1] and negative values count as fully transparent.
+///
+/// Panics if [`try_scale_alpha_to_original_coverage()`] would return an error.
 pub fn scale_alpha_to_original_coverage(
     src: &Image<'_, AlbedoFormat>,
     downsampled: &Image<'_, AlbedoFormat>,
     alpha_cutoff: Option<f32>,
 ) -> Vec<u8> {
-    assert!(
-        matches!(
-            src.format,
-            AlbedoFormat::Rgba8Unorm | AlbedoFormat::Rgba8Snorm
-        ),
-        "Cannot retain alpha coverage on image with no alpha channel"
-    );
+    unwrap_or_panic(try_scale_alpha_to_original_coverage(
+        src,
+        downsampled,
+        alpha_cutoff,
+    ))
+}
+
+/// Version of [`scale_alpha_to_original_coverage()`] which returns an error instead of panicking
+/// if the images have different formats or have no alpha channel.
+pub fn try_scale_alpha_to_original_coverage(
+    src: &Image<'_, AlbedoFormat>,
+    downsampled: &Image<'_, AlbedoFormat>,
+    alpha_cutoff: Option<f32>,
+) -> Result<Vec<u8>, DownsampleError> {
+    let alpha_channel = src
+        .format
+        .alpha_channel()
+        .ok_or(DownsampleError::NoAlphaChannel)?;
+    try_scale_alpha_to_original_coverage_in_channel(src, downsampled, alpha_channel, alpha_cutoff)
+}
+
+/// Version of [`scale_alpha_to_original_coverage()`] which rescales the alpha of `downsampled` in place.
+pub fn scale_alpha_to_original_coverage_into(
+    src: &Image<'_, AlbedoFormat>,
+    downsampled: &mut ImageMut<'_, AlbedoFormat>,
+    alpha_cutoff: Option<f32>,
+) {
+    unwrap_or_panic(try_scale_alpha_to_original_coverage_into(
+        src,
+        downsampled,
+        alpha_cutoff,
+    ))
+}
+
+/// Version of [`scale_alpha_to_original_coverage_into()`] which returns an error instead of panicking.
+pub fn try_scale_alpha_to_original_coverage_into(
+    src: &Image<'_, AlbedoFormat>,
+    downsampled: &mut ImageMut<'_, AlbedoFormat>,
+    alpha_cutoff: Option<f32>,
+) -> Result<(), DownsampleError> {
+    let alpha_channel = src
+        .format
+        .alpha_channel()
+        .ok_or(DownsampleError::NoAlphaChannel)?;
+    try_scale_alpha_to_original_coverage_in_channel_into(
+        src,
+        downsampled,
+        alpha_channel,
+        alpha_cutoff,
+    )
+}
+
+/// Version of [`scale_alpha_to_original_coverage()`] which reads and rescales the alpha in channel `alpha_channel`,
+/// such as for a mask that is stored in the red channel. All other channels are left as they are.
+pub fn scale_alpha_to_original_coverage_in_channel(
+    src: &Image<'_, AlbedoFormat>,
+    downsampled: &Image<'_, AlbedoFormat>,
+    alpha_channel: usize,
+    alpha_cutoff: Option<f32>,
+) -> Vec<u8> {
+    unwrap_or_panic(try_scale_alpha_to_original_coverage_in_channel(
+        src,
+        downsampled,
+        alpha_channel,
+        alpha_cutoff,
+    ))
+}
+
+/// Version of [`scale_alpha_to_original_coverage_in_channel()`] which returns an error instead of panicking
+/// if the images have different formats or if `alpha_channel` is not a channel of their format.
+pub fn try_scale_alpha_to_original_coverage_in_channel(
+    src: &Image<'_, AlbedoFormat>,
+    downsampled: &Image<'_, AlbedoFormat>,
+    alpha_channel: usize,
+    alpha_cutoff: Option<f32>,
+) -> Result<Vec<u8>, DownsampleError> {
     let mut alpha_scaled_data = downsampled.pixels.to_vec();
-    unsafe {
-        ispc::downsample_ispc::scale_to_alpha_coverage(
-            src.width,
-            src.height,
-            src.pixels.as_ptr(),
+    try_scale_alpha_to_original_coverage_in_channel_into(
+        src,
+        &mut ImageMut::new_with_row_pitch(
+            &mut alpha_scaled_data,
             downsampled.width,
             downsampled.height,
-            alpha_scaled_data.as_mut_ptr(),
-            alpha_cutoff
-                .as_ref()
-                .map_or(std::ptr::null(), |alpha_cutoff| alpha_cutoff),
-        );
-    }
-    alpha_scaled_data
+            downsampled.format,
+            downsampled.pixel_stride_in_bytes,
+            downsampled.row_pitch_in_bytes,
+        ),
+        alpha_channel,
+        alpha_cutoff,
+    )?;
+    Ok(alpha_scaled_data)
 }
-// Defines a line of weights. `coefficients` contains a weight for each pixel after `start`
-#[derive(Debug, Clone)]
-struct CachedWeight {
-    pub start: u32,
-    pub coefficients: Rc<Vec<f32>>,
+
+/// Version of [`scale_alpha_to_original_coverage_in_channel()`] which rescales the alpha of `downsampled` in place.
+pub fn scale_alpha_to_original_coverage_in_channel_into(
+    src: &Image<'_, AlbedoFormat>,
+    downsampled: &mut ImageMut<'_, AlbedoFormat>,
+    alpha_channel: usize,
+    alpha_cutoff: Option<f32>,
+) {
+    unwrap_or_panic(try_scale_alpha_to_original_coverage_in_channel_into(
+        src,
+        downsampled,
+        alpha_channel,
+        alpha_cutoff,
+    ))
 }
 
-pub(crate) fn calculate_weights(src: u32, target: u32, filter_scale: f32) -> Vec<CachedWeight> {
-    assert!(
-        src >= target,
-        "Trying to use downsampler to upsample or perform an operation which will cause no changes"
-    );
-    // Every line of weights is based on the start and end of the line, and its "center" which has the biggest weight.
-    // These weight lines follow a pattern, so we can skip calculating some of them by caching all different line we get.
-    // For that purpose, we first determine the variables which define the line.
-    let mut variables = vec![ispc::WeightDimensions::default(); target as usize];
+/// Version of [`scale_alpha_to_original_coverage_in_channel_into()`] which returns an error instead of panicking.
+pub fn try_scale_alpha_to_original_coverage_in_channel_into(
+    src: &Image<'_, AlbedoFormat>,
+    downsampled: &mut ImageMut<'_, AlbedoFormat>,
+    alpha_channel: usize,
+    alpha_cutoff: Option<f32>,
+) -> Result<(), DownsampleError> {
+    let num_channels = src.format.num_channel_in_memory();
+    if alpha_channel >= num_channels {
+        return Err(DownsampleError::ChannelOutOfRange {
+            channel: alpha_channel,
+            num_channels,
+        });
+    }
+    if src.format != downsampled.format {
+        return Err(DownsampleError::FormatMismatch);
+    }
 
+    let alpha_cutoff = alpha_cutoff
+        .as_ref()
+        .map_or(std::ptr::null(), |alpha_cutoff| alpha_cutoff);
     unsafe {
-        ispc::downsample_ispc::calculate_weight_dimensions(
-            filter_scale,
-            src,
-            target,
-            variables.as_mut_ptr(),
+        let coverage = ispc::calculate_alpha_coverage(
+            &src.ispc_representation(),
+            src.format.into(),
+            alpha_channel as u32,
+            alpha_cutoff,
         );
-    }
-
-    let image_scale = src as f32 / target as f32;
-
-    let mut res = Vec::with_capacity(target as usize);
-
-    // We cache the weights in a map so that we can reuse them as we need.
-    // Half of the total number of weights seems like a good starting point to avoid unnecessary copies when resizing.
-    let mut reuse_heap = HashMap::<_, Rc<Vec<f32>>>::with_capacity(target as usize / 2);
-
-    for v in variables.iter() {
-        let coefficient_count = (v.src_end - v.src_start + 1.0) as u32;
-        // The unique values that define a collection of cached weights are how many pixels it includes and the distance from its start to its center.
-        // We use them to create a key based on which we reuse ones we've calculated previously.
-        let reuse_key = (
-            coefficient_count,
-            (v.src_center - v.src_start).to_ne_bytes(),
+        ispc::scale_to_alpha_coverage(
+            coverage,
+            &mut downsampled.ispc_representation(),
+            downsampled.format.into(),
+            alpha_channel as u32,
+            alpha_cutoff,
         );
+    }
+    Ok(())
+}
 
-        let reused = reuse_heap.get(&reuse_key);
-
-        // If there is already a weight line calculated for that key, we clone it since it's an `Rc`.
-        // If there isn't, we calculate the weights and add them to the reuse heap.
-        let coefficients = if let Some(coefficients) = reused {
-            coefficients.clone()
-        } else {
-            let mut coefficients = vec![0.0; coefficient_count as usize];
-            unsafe {
-                ispc::downsample_ispc::calculate_weights_lanczos(
-                    image_scale,
-                    filter_scale,
-                    v as *const _,
-                    coefficients.as_mut_ptr(),
-                );
-            }
-            let coefficients = Rc::new(coefficients);
-            reuse_heap.insert(reuse_key, coefficients.clone());
-            coefficients
-        };
-
-        let cached = CachedWeight {
-            start: v.src_start as u32,
-            coefficients,
-        };
+/// Options that control how [`downsample_with_options()`] filters the image.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DownsampleOptions {
+    /// The filter used along the width of the image.
+    pub horizontal: AxisFilter,
+    /// The filter used along the height of the image. This can differ from [`horizontal`][Self::horizontal],
+    /// such as a sharp horizontal and a soft vertical filter for anamorphic textures.
+    /// When both axes use the same settings and dimensions, their weights are only calculated once.
+    pub vertical: AxisFilter,
+    /// Filters [float formats][AlbedoFormat::is_float()] in a tonemapped space to suppress ringing around very bright pixels.
+    ///
+    /// The negative lobes of filters such as Lanczos around a pixel that is orders of magnitude brighter than its surroundings,
+    /// such as the sun in an environment map, produce dark halos. With this option the color is divided by `1 + max(r, g, b)`
+    /// before filtering and the division is reverted afterwards, which bounds the contribution of every pixel.
+    /// This also clamps negative colors to 0. Alpha is not affected.
+    ///
+    /// Has no effect on formats which are not float formats. Defaults to `false`.
+    pub suppress_hdr_ringing: bool,
+    /// Clamps every channel of every filtered pixel to the minimum and maximum of the source pixels in its footprint.
+    ///
+    /// Filters with negative lobes, such as Lanczos, overshoot around high-contrast edges, which shows up as halos
+    /// around text and UI elements. The clamp is applied in both separable passes, so the result never leaves the range
+    /// of the source pixels that contribute to it. This removes the halos, but also some of the sharpening the negative
+    /// lobes provide. Filters without negative lobes never overshoot, so it has no effect on them. Defaults to `false`.
+    pub clamp_to_footprint: bool,
+}
 
-        res.push(cached);
+impl DownsampleOptions {
+    /// Returns the default options with `filter` along both axes.
+    pub fn with_filter(filter: impl Into<AxisFilter>) -> Self {
+        let filter = filter.into();
+        Self {
+            horizontal: filter,
+            vertical: filter,
+            ..Default::default()
+        }
     }
-
-    res
 }
 
 /// Samples the provided image down to the specified width and height.
 /// `target_width` and `target_height` are expected to be less than or equal to their `src` counter parts.
 /// Will panic if the target dimensions are the same as the source image's.
 ///
+/// The returned `Vec` is tightly packed: every pixel takes [`pixel_size_in_bytes()`][ImagePixelFormat::pixel_size_in_bytes()] bytes
+/// and every row takes `target_width` pixels, regardless of the pixel stride of `src`.
+/// To write the result with a different pixel stride or row pitch, use [`downsample_into()`] with an [`ImageMut`].
+/// The same layout is returned by all `Vec` returning versions of this function.
+///
 /// For a more fine-tunable version of this function, see [downsample_with_custom_scale].
+/// For a version which returns an error instead of panicking, see [`try_downsample()`].
 pub fn downsample(src: &Image<'_, AlbedoFormat>, target_width: u32, target_height: u32) -> Vec<u8> {
     downsample_with_custom_scale(src, target_width, target_height, 3.0)
 }
 
-fn precompute_lanczos_weights(
+/// Version of [`downsample()`] which returns an error instead of panicking
+/// if the target dimensions cannot be downsampled to.
+pub fn try_downsample(
+    src: &Image<'_, AlbedoFormat>,
+    target_width: u32,
+    target_height: u32,
+) -> Result<Vec<u8>, DownsampleError> {
+    try_downsample_with_custom_scale(src, target_width, target_height, 3.0)
+}
+
+/// Version of [`downsample()`] which writes to `dst` instead of returning a `Vec`.
+/// The target dimensions are the dimensions of `dst`, which must have the same format as `src`.
+pub fn downsample_into(src: &Image<'_, AlbedoFormat>, dst: &mut ImageMut<'_, AlbedoFormat>) {
+    downsample_with_custom_scale_into(src, dst, 3.0)
+}
+
+/// Version of [`downsample_into()`] which returns an error instead of panicking.
+pub fn try_downsample_into(
+    src: &Image<'_, AlbedoFormat>,
+    dst: &mut ImageMut<'_, AlbedoFormat>,
+) -> Result<(), DownsampleError> {
+    try_downsample_with_custom_scale_into(src, dst, 3.0)
+}
+
+/// Checks that `src_width` x `src_height` can be downsampled to `dst_width` x `dst_height`.
+fn check_dimensions(
     src_width: u32,
     src_height: u32,
     dst_width: u32,
     dst_height: u32,
-    filter_scale: f32,
-) -> ispc::Weights {
-    assert!(src_width != dst_width || src_height != dst_height, "Trying to downsample to an image of the same resolution as the source image. This operation can be avoided.");
-    assert!(src_width >= dst_width, "The width of the source image is less than the target's width. You are trying to upsample rather than downsample");
-    assert!(src_height >= dst_height, "The height of the source image is less than the target's height. You are trying to upsample rather than downsample");
-    assert!(
-        filter_scale > 0.0,
-        "filter_scale must be more than 0.0 when downsampling."
-    );
-
-    // The weights are calculated per-axis, and are only based on the source and target dimensions of that axis.
-    // Because of that, if both axes have the same source and target dimensions, they will have the same weights.
-    let width_weights =
-        WeightCollection::new(calculate_weights(src_width, dst_width, filter_scale));
-    let height_weights = if src_width == src_height && dst_width == dst_height {
+) -> Result<(), DownsampleError> {
+    if src_width == 0 || src_height == 0 {
+        return Err(DownsampleError::EmptyImage {
+            width: src_width,
+            height: src_height,
+        });
+    }
+    if dst_width == 0 || dst_height == 0 {
+        return Err(DownsampleError::EmptyImage {
+            width: dst_width,
+            height: dst_height,
+        });
+    }
+    if src_width == dst_width && src_height == dst_height {
+        return Err(DownsampleError::SameResolution {
+            width: src_width,
+            height: src_height,
+        });
+    }
+    if src_width < dst_width || src_height < dst_height {
+        return Err(DownsampleError::Upsampling {
+            src_width,
+            src_height,
+            target_width: dst_width,
+            target_height: dst_height,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that the settings of `filter` are valid.
+fn check_axis_filter(filter: &AxisFilter) -> Result<(), DownsampleError> {
+    let filter_scale = filter.filter_scale;
+    // Also rejects NaN, which fails every comparison.
+    if !(filter_scale > 0.0 && filter_scale.is_finite()) {
+        return Err(DownsampleError::InvalidFilterScale(filter_scale));
+    }
+    if let Filter::Lanczos { lobes } = filter.filter {
+        if !(lobes > 0.0 && lobes.is_finite()) {
+            return Err(DownsampleError::InvalidLanczosLobes(lobes));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the support of `filter` is valid.
+fn check_custom_filter(filter: &dyn ResampleFilter) -> Result<(), DownsampleError> {
+    let support = filter.support();
+    if !(support > 0.0 && support.is_finite()) {
+        return Err(DownsampleError::InvalidFilterSupport(support));
+    }
+    Ok(())
+}
+
+fn precompute_weights(
+    src_width: u32,
+    src_height: u32,
+    dst_width: u32,
+    dst_height: u32,
+    horizontal: &AxisFilter,
+    vertical: &AxisFilter,
+) -> Result<Weights, DownsampleError> {
+    check_dimensions(src_width, src_height, dst_width, dst_height)?;
+    check_axis_filter(horizontal)?;
+    check_axis_filter(vertical)?;
+
+    precompute_filter_weights(
+        (src_width, dst_width, horizontal),
+        (src_height, dst_height, vertical),
+        horizontal == vertical,
+        calculate_axis_weights,
+    )
+}
+
+fn precompute_custom_weights(
+    src_width: u32,
+    src_height: u32,
+    dst_width: u32,
+    dst_height: u32,
+    horizontal: &dyn ResampleFilter,
+    vertical: &dyn ResampleFilter,
+) -> Result<Weights, DownsampleError> {
+    check_dimensions(src_width, src_height, dst_width, dst_height)?;
+    check_custom_filter(horizontal)?;
+    check_custom_filter(vertical)?;
+
+    // Custom filters cannot be compared, so only the weights of the very same filter are shared.
+    // Zero-sized filters of different types can share their address, so the vtables are compared as well.
+    let same_filter = std::ptr::eq(horizontal, vertical);
+    precompute_filter_weights(
+        (src_width, dst_width, horizontal),
+        (src_height, dst_height, vertical),
+        same_filter,
+        calculate_weights,
+    )
+}
+
+/// Calculates the weights of both axes, each given as the source and target size and the filter along that axis.
+/// `same_filter` tells whether both filters produce the same weights for the same dimensions.
+fn precompute_filter_weights<F: ?Sized>(
+    (src_width, dst_width, horizontal): (u32, u32, &F),
+    (src_height, dst_height, vertical): (u32, u32, &F),
+    same_filter: bool,
+    calculate_weights: impl Fn(u32, u32, &F) -> Result<WeightCollection, DownsampleError>,
+) -> Result<Weights, DownsampleError> {
+    // The weights are calculated per-axis, and are only based on the source and target dimensions and the filter of that axis.
+    // Because of that, if both axes have the same source and target dimensions and filter, they will have the same weights.
+    let width_weights = Arc::new(calculate_weights(src_width, dst_width, horizontal)?);
+    let height_weights = if same_filter && src_width == src_height && dst_width == dst_height {
         width_weights.clone()
     } else {
-        WeightCollection::new(calculate_weights(src_height, dst_height, filter_scale))
+        Arc::new(calculate_weights(src_height, dst_height, vertical)?)
     };
 
-    ispc::Weights::new(width_weights, height_weights)
+    Ok(Weights::new(width_weights, height_weights))
 }
 
 /// Version of [downsample] which allows for a custom filter scale, thus trading between speed and final image quality.
 ///
-/// `filter_scale` controls how many samples are made relative to the size ratio between the source and target resolutions.
+/// `filter_scale` is the number of lobes of the Lanczos filter, see [`Filter::Lanczos`],
+/// which controls how many samples are made relative to the size ratio between the source and target resolutions.
 /// The higher the scale, the more detail is preserved, but the slower the downsampling is. Note that the effect on the detail becomes smaller the higher the scale is.
 ///
 /// As a guideline, a `filter_scale` of 3.0 preserves detail well.
@@ -289,103 +990,346 @@ pub fn downsample_with_custom_scale(
     target_height: u32,
     filter_scale: f32,
 ) -> Vec<u8> {
-    assert!(src.format.pixel_size_in_bytes() <= src.pixel_stride_in_bytes, "The stride between the pixels cannot be lower than the minimum size of the pixel according to the pixel format.");
+    unwrap_or_panic(try_downsample_with_custom_scale(
+        src,
+        target_width,
+        target_height,
+        filter_scale,
+    ))
+}
+
+/// Version of [`downsample_with_custom_scale()`] which returns an error instead of panicking.
+pub fn try_downsample_with_custom_scale(
+    src: &Image<'_, AlbedoFormat>,
+    target_width: u32,
+    target_height: u32,
+    filter_scale: f32,
+) -> Result<Vec<u8>, DownsampleError> {
+    try_downsample_with_options(
+        src,
+        target_width,
+        target_height,
+        &DownsampleOptions::with_filter(Filter::Lanczos {
+            lobes: filter_scale,
+        }),
+    )
+}
+
+/// Version of [`downsample_with_custom_scale()`] which writes to `dst` instead of returning a `Vec`.
+pub fn downsample_with_custom_scale_into(
+    src: &Image<'_, AlbedoFormat>,
+    dst: &mut ImageMut<'_, AlbedoFormat>,
+    filter_scale: f32,
+) {
+    unwrap_or_panic(try_downsample_with_custom_scale_into(
+        src,
+        dst,
+        filter_scale,
+    ))
+}
+
+/// Version of [`downsample_with_custom_scale_into()`] which returns an error instead of panicking.
+pub fn try_downsample_with_custom_scale_into(
+    src: &Image<'_, AlbedoFormat>,
+    dst: &mut ImageMut<'_, AlbedoFormat>,
+    filter_scale: f32,
+) -> Result<(), DownsampleError> {
+    try_downsample_with_options_into(
+        src,
+        dst,
+        &DownsampleOptions::with_filter(Filter::Lanczos {
+            lobes: filter_scale,
+        }),
+    )
+}
+
+/// Version of [downsample] which takes [`DownsampleOptions`] to control the filtering.
+///
+/// To downsample many images of the same resolution, use a [`Downsampler`] instead.
+pub fn downsample_with_options(
+    src: &Image<'_, AlbedoFormat>,
+    target_width: u32,
+    target_height: u32,
+    options: &DownsampleOptions,
+) -> Vec<u8> {
+    unwrap_or_panic(try_downsample_with_options(
+        src,
+        target_width,
+        target_height,
+        options,
+    ))
+}
+
+/// Version of [`downsample_with_options()`] which returns an error instead of panicking.
+pub fn try_downsample_with_options(
+    src: &Image<'_, AlbedoFormat>,
+    target_width: u32,
+    target_height: u32,
+    options: &DownsampleOptions,
+) -> Result<Vec<u8>, DownsampleError> {
+    Downsampler::try_new(src.width, src.height, target_width, target_height, options)?
+        .try_downsample(src)
+}
+
+/// Version of [`downsample_with_options()`] which writes to `dst` instead of returning a `Vec`.
+pub fn downsample_with_options_into(
+    src: &Image<'_, AlbedoFormat>,
+    dst: &mut ImageMut<'_, AlbedoFormat>,
+    options: &DownsampleOptions,
+) {
+    unwrap_or_panic(try_downsample_with_options_into(src, dst, options))
+}
+
+/// Version of [`downsample_with_options_into()`] which returns an error instead of panicking.
+pub fn try_downsample_with_options_into(
+    src: &Image<'_, AlbedoFormat>,
+    dst: &mut ImageMut<'_, AlbedoFormat>,
+    options: &DownsampleOptions,
+) -> Result<(), DownsampleError> {
+    Downsampler::try_new(src.width, src.height, dst.width, dst.height, options)?
+        .try_downsample_into(src, dst)
+}
+
+/// Version of [`downsample_with_options()`] which filters with the custom `filter`, see [`ResampleFilter`].
+///
+/// The [`horizontal`][DownsampleOptions::horizontal] and [`vertical`][DownsampleOptions::vertical] filters of `options`
+/// are ignored, the other options are used as usual.
+/// To downsample many images of the same resolution, use [`Downsampler::new_with_filter()`] instead.
+pub fn downsample_with_filter(
+    src: &Image<'_, AlbedoFormat>,
+    target_width: u32,
+    target_height: u32,
+    filter: &dyn ResampleFilter,
+    options: &DownsampleOptions,
+) -> Vec<u8> {
+    unwrap_or_panic(try_downsample_with_filter(
+        src,
+        target_width,
+        target_height,
+        filter,
+        options,
+    ))
+}
 
-    let sample_weights = precompute_lanczos_weights(
+/// Version of [`downsample_with_filter()`] which returns an error instead of panicking.
+pub fn try_downsample_with_filter(
+    src: &Image<'_, AlbedoFormat>,
+    target_width: u32,
+    target_height: u32,
+    filter: &dyn ResampleFilter,
+    options: &DownsampleOptions,
+) -> Result<Vec<u8>, DownsampleError> {
+    Downsampler::try_new_with_filter(
         src.width,
         src.height,
         target_width,
         target_height,
-        filter_scale,
-    );
+        filter,
+        options,
+    )?
+    .try_downsample(src)
+}
 
-    // The new implementation needs a src_height * target_width intermediate buffer.
-    let mut scratch_space =
-        vec![0u8; (src.height * target_width * src.format.num_channel_in_memory() as u32) as usize];
+/// Version of [`downsample_with_filter()`] which writes to `dst` instead of returning a `Vec`.
+pub fn downsample_with_filter_into(
+    src: &Image<'_, AlbedoFormat>,
+    dst: &mut ImageMut<'_, AlbedoFormat>,
+    filter: &dyn ResampleFilter,
+    options: &DownsampleOptions,
+) {
+    unwrap_or_panic(try_downsample_with_filter_into(src, dst, filter, options))
+}
 
-    let mut output = vec![
-        0u8;
-        (target_width * target_height * src.format.num_channel_in_memory() as u32)
-            as usize
-    ];
+/// Version of [`downsample_with_filter_into()`] which returns an error instead of panicking.
+pub fn try_downsample_with_filter_into(
+    src: &Image<'_, AlbedoFormat>,
+    dst: &mut ImageMut<'_, AlbedoFormat>,
+    filter: &dyn ResampleFilter,
+    options: &DownsampleOptions,
+) -> Result<(), DownsampleError> {
+    Downsampler::try_new_with_filter(
+        src.width, src.height, dst.width, dst.height, filter, options,
+    )?
+    .try_downsample_into(src, dst)
+}
 
-    unsafe {
-        if src.format.num_channel_in_memory() == 3 {
-            ispc::downsample_ispc::resample_with_cached_weights_3(
-                &ispc::SourceImage {
-                    width: src.width,
-                    height: src.height,
-                    data: src.pixels.as_ptr(),
-                    pixel_stride: src.pixel_stride_in_bytes as u32,
-                },
-                &mut ispc::DownsampledImage {
-                    width: target_width,
-                    height: target_height,
-                    data: output.as_mut_ptr(),
-                    pixel_stride: src.pixel_stride_in_bytes as u32,
-                },
-                ispc::PixelFormat::from(src.format),
-                &mut ispc::DownsamplingContext {
-                    weights: *sample_weights.ispc_representation(),
-                    scratch_space: scratch_space.as_mut_ptr(),
-                },
-            );
-        } else {
-            ispc::downsample_ispc::resample_with_cached_weights_4(
-                &ispc::SourceImage {
-                    width: src.width,
-                    height: src.height,
-                    data: src.pixels.as_ptr(),
-                    pixel_stride: src.pixel_stride_in_bytes as u32,
-                },
-                &mut ispc::DownsampledImage {
-                    width: target_width,
-                    height: target_height,
-                    data: output.as_mut_ptr(),
-                    pixel_stride: src.pixel_stride_in_bytes as u32,
-                },
-                ispc::PixelFormat::from(src.format),
-                &mut ispc::DownsamplingContext {
-                    weights: *sample_weights.ispc_representation(),
-                    scratch_space: scratch_space.as_mut_ptr(),
-                },
-            );
+/// The filter that [`downsample_normal_map_with_options()`] uses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NormalMapFilter {
+    /// Averages all normals in the footprint of a pixel.
+    Box,
+    /// Filters the normals with a Lanczos filter with `lobes` lobes, see [`Filter::Lanczos`], which keeps more detail.
+    ///
+    /// The negative lobes of the filter can make normals that point in different directions cancel out.
+    /// Where the filtered vector is too short to be normalized, the normal `(0, 0, 1)` is written instead.
+    Lanczos { lobes: f32 },
+}
+
+/// Options that control how [`downsample_normal_map_with_options()`] filters the normal map.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NormalMapOptions {
+    /// Defaults to [`NormalMapFilter::Box`].
+    pub filter: NormalMapFilter,
+}
+
+impl Default for NormalMapOptions {
+    fn default() -> Self {
+        Self {
+            filter: NormalMapFilter::Box,
         }
     }
-
-    output
 }
 
 /// Downsamples an image that is meant to be used as a normal map.
 /// Uses a box filter instead of a lanczos filter, and normalizes each pixel to preserve unit length for the normals after downsampling.
+/// To filter normal maps with a lanczos filter instead, see [`downsample_normal_map_with_options()`].
 ///
-/// Returns a `Vec` with the downsampled data. If `normal_map_format.pixel_size() < pixel_stride_in_bytes`, the `Vec` will contain more values than channels than the format has specified, with all pixels in them initialized to 255.
+/// Returns a `Vec` with the downsampled data. Unlike [`downsample()`], the pixels in the `Vec` keep the pixel stride of `src`, with tightly packed rows.
+/// If `normal_map_format.pixel_size() < pixel_stride_in_bytes`, the `Vec` will contain more values than channels than the format has specified, with all pixels in them initialized to 255.
+/// To write the result with a different pixel stride or row pitch, use [`downsample_normal_map_into()`] with an [`ImageMut`].
+///
+/// Panics if [`try_downsample_normal_map()`] would return an error.
 pub fn downsample_normal_map(
     src: &Image<'_, NormalMapFormat>,
     target_width: u32,
     target_height: u32,
 ) -> Vec<u8> {
-    assert!(src.format.pixel_size_in_bytes() <= src.pixel_stride_in_bytes, "The pixel stride in bytes must be more or equal than the size of a single pixel as described by the format of the normal map.");
+    unwrap_or_panic(try_downsample_normal_map(src, target_width, target_height))
+}
 
-    let mut data = vec![255u8; (target_width * target_height) as usize * src.pixel_stride_in_bytes];
+/// Version of [`downsample_normal_map()`] which returns an error instead of panicking
+/// if the target dimensions are empty.
+pub fn try_downsample_normal_map(
+    src: &Image<'_, NormalMapFormat>,
+    target_width: u32,
+    target_height: u32,
+) -> Result<Vec<u8>, DownsampleError> {
+    try_downsample_normal_map_with_options(
+        src,
+        target_width,
+        target_height,
+        &NormalMapOptions::default(),
+    )
+}
 
-    unsafe {
-        ispc::downsample_normal_map(
-            &ispc::SourceImage {
-                width: src.width,
-                height: src.height,
-                data: src.pixels.as_ptr(),
-                pixel_stride: src.pixel_stride_in_bytes as u32,
-            },
-            &mut ispc::DownsampledImage {
-                width: target_width,
-                height: target_height,
-                data: data.as_mut_ptr(),
-                pixel_stride: src.pixel_stride_in_bytes as u32,
-            },
-            ispc::NormalMapFormat::from(src.format),
-        );
+/// Version of [`downsample_normal_map()`] which writes to `dst` instead of returning a `Vec`.
+/// The target dimensions are the dimensions of `dst`, which must have the same format as `src`.
+pub fn downsample_normal_map_into(
+    src: &Image<'_, NormalMapFormat>,
+    dst: &mut ImageMut<'_, NormalMapFormat>,
+) {
+    unwrap_or_panic(try_downsample_normal_map_into(src, dst))
+}
+
+/// Version of [`downsample_normal_map_into()`] which returns an error instead of panicking.
+pub fn try_downsample_normal_map_into(
+    src: &Image<'_, NormalMapFormat>,
+    dst: &mut ImageMut<'_, NormalMapFormat>,
+) -> Result<(), DownsampleError> {
+    try_downsample_normal_map_with_options_into(src, dst, &NormalMapOptions::default())
+}
+
+/// Version of [`downsample_normal_map()`] which takes [`NormalMapOptions`] to control the filtering.
+pub fn downsample_normal_map_with_options(
+    src: &Image<'_, NormalMapFormat>,
+    target_width: u32,
+    target_height: u32,
+    options: &NormalMapOptions,
+) -> Vec<u8> {
+    unwrap_or_panic(try_downsample_normal_map_with_options(
+        src,
+        target_width,
+        target_height,
+        options,
+    ))
+}
+
+/// Version of [`downsample_normal_map_with_options()`] which returns an error instead of panicking
+/// if the target dimensions are empty, or if the Lanczos filter cannot downsample to them.
+pub fn try_downsample_normal_map_with_options(
+    src: &Image<'_, NormalMapFormat>,
+    target_width: u32,
+    target_height: u32,
+    options: &NormalMapOptions,
+) -> Result<Vec<u8>, DownsampleError> {
+    let mut data = vec![
+        255u8;
+        (target_width as usize * target_height as usize)
+            .saturating_mul(src.pixel_stride_in_bytes)
+    ];
+
+    try_downsample_normal_map_with_options_into(
+        src,
+        &mut ImageMut::try_new_with_pixel_stride(
+            &mut data,
+            target_width,
+            target_height,
+            src.format,
+            src.pixel_stride_in_bytes,
+        )?,
+        options,
+    )?;
+
+    Ok(data)
+}
+
+/// Version of [`downsample_normal_map_with_options()`] which writes to `dst` instead of returning a `Vec`.
+pub fn downsample_normal_map_with_options_into(
+    src: &Image<'_, NormalMapFormat>,
+    dst: &mut ImageMut<'_, NormalMapFormat>,
+    options: &NormalMapOptions,
+) {
+    unwrap_or_panic(try_downsample_normal_map_with_options_into(
+        src, dst, options,
+    ))
+}
+
+/// Version of [`downsample_normal_map_with_options_into()`] which returns an error instead of panicking.
+pub fn try_downsample_normal_map_with_options_into(
+    src: &Image<'_, NormalMapFormat>,
+    dst: &mut ImageMut<'_, NormalMapFormat>,
+    options: &NormalMapOptions,
+) -> Result<(), DownsampleError> {
+    if src.format != dst.format {
+        return Err(DownsampleError::FormatMismatch);
     }
 
-    data
+    match options.filter {
+        NormalMapFilter::Box => unsafe {
+            ispc::downsample_normal_map(
+                &src.ispc_representation(),
+                &mut dst.ispc_representation(),
+                ispc::NormalMapFormat::from(src.format),
+            );
+        },
+        NormalMapFilter::Lanczos { lobes } => {
+            let filter = AxisFilter::from(Filter::Lanczos { lobes });
+            let weights = precompute_weights(
+                src.width, src.height, dst.width, dst.height, &filter, &filter,
+            )?;
+
+            // The horizontal pass writes a src_height * target_width intermediate buffer of normals.
+            let mut scratch_space = vec![0.0f32; src.height as usize * dst.width as usize * 3];
+            let horizontal_weights = weights.horizontal_weights().ispc_representation();
+            let vertical_weights = weights.vertical_weights().ispc_representation();
+            unsafe {
+                ispc::resample_normal_map_with_cached_weights(
+                    &src.ispc_representation(),
+                    &mut dst.ispc_representation(),
+                    ispc::NormalMapFormat::from(src.format),
+                    &mut ispc::DownsamplingContext {
+                        weights: ispc::SampleWeights {
+                            vertical_weights: &vertical_weights,
+                            horizontal_weights: &horizontal_weights,
+                        },
+                        scratch_space: scratch_space.as_mut_ptr(),
+                        // The normals are renormalized afterwards, so clamping the components separately would only bend them.
+                        clamp_to_footprint: false,
+                        suppress_hdr_ringing: false,
+                    },
+                );
+            }
+        }
+    }
+    Ok(())
 }
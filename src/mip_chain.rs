@@ -0,0 +1,413 @@
+//! Generation of complete mip chains into a single buffer.
+
+use crate::{
+    error::unwrap_or_panic, ispc, AlbedoFormat, DownsampleError, DownsampleOptions, Downsampler,
+    Image, ImageMut, ImagePixelFormat, NormalMapFormat, ResampleFilter,
+};
+
+/// The image from which every level of a mip chain is filtered.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum MipSource {
+    /// Filters every level from the previous level, which is faster as the source becomes smaller with every level.
+    PreviousLevel,
+    /// Filters every level from the base image, which avoids accumulating the filtering error of the previous levels
+    /// at the cost of larger filter footprints for the small levels.
+    BaseImage,
+}
+
+/// Options that control how [`generate_mip_chain()`] generates the levels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MipOptions {
+    /// The options used to downsample every level.
+    pub downsample_options: DownsampleOptions,
+    /// Which image every level is filtered from. Defaults to [`MipSource::PreviousLevel`].
+    pub source: MipSource,
+    /// Rescales the alpha of every level so that its alpha coverage matches the coverage of the base level,
+    /// as [`scale_alpha_to_original_coverage()`][crate::scale_alpha_to_original_coverage()] does.
+    /// This keeps alpha-tested geometry such as foliage from thinning out in the smaller levels.
+    ///
+    /// The coverage of the base level is only calculated once. With [`MipSource::PreviousLevel`],
+    /// every level is filtered from the rescaled previous level.
+    /// Requires a format with an [alpha channel][AlbedoFormat::alpha_channel()].
+    /// Defaults to `false`.
+    pub preserve_alpha_coverage: bool,
+    /// The alpha cutoff used to calculate the coverage when [`preserve_alpha_coverage`][Self::preserve_alpha_coverage] is set.
+    /// See [`scale_alpha_to_original_coverage()`][crate::scale_alpha_to_original_coverage()] for how it is used. Defaults to `None`.
+    pub alpha_cutoff: Option<f32>,
+}
+
+impl Default for MipOptions {
+    fn default() -> Self {
+        Self {
+            downsample_options: DownsampleOptions::default(),
+            source: MipSource::PreviousLevel,
+            preserve_alpha_coverage: false,
+            alpha_cutoff: None,
+        }
+    }
+}
+
+/// Options that control how [`generate_normal_map_mip_chain()`] generates the levels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NormalMapMipOptions {
+    /// Which normals every level is filtered from. Defaults to [`MipSource::PreviousLevel`].
+    ///
+    /// The levels are filtered from the unnormalized averages of the previous level rather than from the stored normals,
+    /// so the variance of every level describes all base level normals in its footprint with either source.
+    pub source: MipSource,
+    /// Outputs the variance of the normals in the footprint of every pixel, see [`MipChain::level_variance()`]. Defaults to `false`.
+    pub output_variance: bool,
+}
+
+impl Default for NormalMapMipOptions {
+    fn default() -> Self {
+        Self {
+            source: MipSource::PreviousLevel,
+            output_variance: false,
+        }
+    }
+}
+
+/// Describes where a single level is stored in the buffer of a [`MipChain`].
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct MipLevel {
+    pub width: u32,
+    pub height: u32,
+    pub offset_in_bytes: usize,
+    pub size_in_bytes: usize,
+}
+
+/// All levels of a mip chain, stored tightly packed one after another in a single buffer.
+///
+/// Level 0 is a copy of the base image and every following level halves the dimensions of the previous level,
+/// rounding down and clamping to 1 like D3D and Vulkan do, until the last level is 1x1.
+#[derive(Clone, Debug)]
+pub struct MipChain<F: ImagePixelFormat = AlbedoFormat> {
+    data: Vec<u8>,
+    levels: Vec<MipLevel>,
+    format: F,
+    // One value per pixel of every level, only filled for normal maps when requested.
+    variance: Vec<f32>,
+}
+
+impl<F: ImagePixelFormat> MipChain<F> {
+    /// Returns the levels of the chain, starting at the base level.
+    pub fn levels(&self) -> &[MipLevel] {
+        &self.levels
+    }
+
+    /// Returns the format of every level in the chain.
+    pub fn format(&self) -> F {
+        self.format
+    }
+
+    /// Returns the pixels of all levels.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the buffer with the pixels of all levels.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Returns the tightly packed pixels of level `index`.
+    pub fn level_data(&self, index: usize) -> &[u8] {
+        let level = &self.levels[index];
+        &self.data[level.offset_in_bytes..level.offset_in_bytes + level.size_in_bytes]
+    }
+
+    /// Returns level `index` as an [`Image`], so that it can be passed to the other functions of this crate.
+    pub fn level_image(&self, index: usize) -> Image<'_, F> {
+        let level = &self.levels[index];
+        Image::new(
+            self.level_data(index),
+            level.width,
+            level.height,
+            self.format,
+        )
+    }
+}
+
+impl MipChain<NormalMapFormat> {
+    /// Returns the variance of the normals in the footprint of every pixel of level `index`,
+    /// or `None` if [`NormalMapMipOptions::output_variance`] was not set.
+    ///
+    /// The variance is calculated from the length `l` of the average normal before it is normalized as `(1 - l) / l`,
+    /// following Toksvig's "Mipmapping Normal Maps". It is 0 for the base level and wherever all normals in the footprint
+    /// point in the same direction. To reduce specular aliasing, the roughness of the matching level of a roughness map
+    /// can be widened with it, which is `alpha' = sqrt(alpha^2 + 2 * variance)` for a Beckmann or GGX `alpha`.
+    pub fn level_variance(&self, index: usize) -> Option<&[f32]> {
+        if self.variance.is_empty() {
+            return None;
+        }
+        let level = &self.levels[index];
+        let pixel_size = self.format.pixel_size_in_bytes();
+        let start = level.offset_in_bytes / pixel_size;
+        Some(&self.variance[start..start + level.width as usize * level.height as usize])
+    }
+}
+
+/// Returns the dimensions of every level of a mip chain with a base level of `width` x `height`.
+fn mip_levels(width: u32, height: u32, pixel_size_in_bytes: usize) -> Vec<MipLevel> {
+    let mut levels = Vec::new();
+    let (mut width, mut height) = (width, height);
+    let mut offset_in_bytes = 0;
+    loop {
+        let size_in_bytes = width as usize * height as usize * pixel_size_in_bytes;
+        levels.push(MipLevel {
+            width,
+            height,
+            offset_in_bytes,
+            size_in_bytes,
+        });
+        offset_in_bytes += size_in_bytes;
+
+        if width == 1 && height == 1 {
+            return levels;
+        }
+        width = (width / 2).max(1);
+        height = (height / 2).max(1);
+    }
+}
+
+/// Allocates the buffer for all `levels` and copies the base image into level 0, removing the stride between its pixels.
+fn allocate_with_base_level<F: ImagePixelFormat>(
+    src: &Image<'_, F>,
+    levels: &[MipLevel],
+) -> Vec<u8> {
+    let total_size = levels
+        .last()
+        .map_or(0, |level| level.offset_in_bytes + level.size_in_bytes);
+    let mut data = vec![0u8; total_size];
+
+    let pixel_size = src.format.pixel_size_in_bytes();
+    for y in 0..src.height as usize {
+        for x in 0..src.width as usize {
+            let read_address = src.pixel_offset(x, y);
+            let write_address = (y * src.width as usize + x) * pixel_size;
+            data[write_address..write_address + pixel_size]
+                .copy_from_slice(&src.pixels[read_address..read_address + pixel_size]);
+        }
+    }
+
+    data
+}
+
+/// Generates every level of the mip chain of `src`, down to 1x1.
+///
+/// Panics if [`try_generate_mip_chain()`] would return an error.
+pub fn generate_mip_chain(src: &Image<'_, AlbedoFormat>, options: &MipOptions) -> MipChain {
+    unwrap_or_panic(try_generate_mip_chain(src, options))
+}
+
+/// Version of [`generate_mip_chain()`] which returns an error instead of panicking
+/// if the filter scale in `options` is invalid, or if alpha coverage should be preserved for a format without alpha.
+pub fn try_generate_mip_chain(
+    src: &Image<'_, AlbedoFormat>,
+    options: &MipOptions,
+) -> Result<MipChain, DownsampleError> {
+    generate_mip_chain_with(src, options, |src_width, src_height, width, height| {
+        Downsampler::try_new(
+            src_width,
+            src_height,
+            width,
+            height,
+            &options.downsample_options,
+        )
+    })
+}
+
+/// Version of [`generate_mip_chain()`] which filters every level with the custom `filter`,
+/// like [`Downsampler::new_with_filter()`] does.
+///
+/// The [`horizontal`][DownsampleOptions::horizontal] and [`vertical`][DownsampleOptions::vertical] filters of
+/// [`downsample_options`][MipOptions::downsample_options] are ignored, the other options are used as usual.
+///
+/// Panics if [`try_generate_mip_chain_with_filter()`] would return an error.
+pub fn generate_mip_chain_with_filter(
+    src: &Image<'_, AlbedoFormat>,
+    filter: &dyn ResampleFilter,
+    options: &MipOptions,
+) -> MipChain {
+    unwrap_or_panic(try_generate_mip_chain_with_filter(src, filter, options))
+}
+
+/// Version of [`generate_mip_chain_with_filter()`] which returns an error instead of panicking
+/// if the [support][ResampleFilter::support()] of `filter` is invalid, or if alpha coverage should be preserved for a format without alpha.
+pub fn try_generate_mip_chain_with_filter(
+    src: &Image<'_, AlbedoFormat>,
+    filter: &dyn ResampleFilter,
+    options: &MipOptions,
+) -> Result<MipChain, DownsampleError> {
+    generate_mip_chain_with(src, options, |src_width, src_height, width, height| {
+        Downsampler::try_new_with_filter(
+            src_width,
+            src_height,
+            width,
+            height,
+            filter,
+            &options.downsample_options,
+        )
+    })
+}
+
+/// Generates the mip chain of `src`, filtering every level with the [`Downsampler`] that `new_downsampler` creates
+/// for the dimensions of its source and of the level.
+fn generate_mip_chain_with(
+    src: &Image<'_, AlbedoFormat>,
+    options: &MipOptions,
+    new_downsampler: impl Fn(u32, u32, u32, u32) -> Result<Downsampler, DownsampleError>,
+) -> Result<MipChain, DownsampleError> {
+    let format = src.format;
+    let alpha_cutoff = options
+        .alpha_cutoff
+        .as_ref()
+        .map_or(std::ptr::null(), |alpha_cutoff| alpha_cutoff);
+    let coverage = if options.preserve_alpha_coverage {
+        let alpha_channel = format
+            .alpha_channel()
+            .ok_or(DownsampleError::NoAlphaChannel)?;
+        let coverage = unsafe {
+            ispc::calculate_alpha_coverage(
+                &src.ispc_representation(),
+                format.into(),
+                alpha_channel as u32,
+                alpha_cutoff,
+            )
+        };
+        Some((alpha_channel, coverage))
+    } else {
+        None
+    };
+
+    let pixel_size = format.pixel_size_in_bytes();
+    let levels = mip_levels(src.width, src.height, pixel_size);
+    let mut data = allocate_with_base_level(src, &levels);
+
+    for (index, level) in levels.iter().enumerate().skip(1) {
+        // Every level is written after the levels before it, so the previous levels can be read while writing it.
+        let (previous_levels, remaining) = data.split_at_mut(level.offset_in_bytes);
+        let mut dst = ImageMut::new(
+            &mut remaining[..level.size_in_bytes],
+            level.width,
+            level.height,
+            format,
+        );
+
+        let previous_image;
+        let level_src = match options.source {
+            MipSource::BaseImage => src,
+            MipSource::PreviousLevel => {
+                let previous = &levels[index - 1];
+                previous_image = Image::new(
+                    &previous_levels[previous.offset_in_bytes..],
+                    previous.width,
+                    previous.height,
+                    format,
+                );
+                &previous_image
+            }
+        };
+
+        new_downsampler(level_src.width, level_src.height, level.width, level.height)?
+            .try_downsample_into(level_src, &mut dst)?;
+
+        if let Some((alpha_channel, coverage)) = coverage {
+            unsafe {
+                ispc::scale_to_alpha_coverage(
+                    coverage,
+                    &mut dst.ispc_representation(),
+                    format.into(),
+                    alpha_channel as u32,
+                    alpha_cutoff,
+                );
+            }
+        }
+    }
+
+    Ok(MipChain {
+        data,
+        levels,
+        format,
+        variance: Vec::new(),
+    })
+}
+
+/// Generates every level of the mip chain of the normal map `src`, down to 1x1.
+///
+/// Like [`downsample_normal_map()`][crate::downsample_normal_map()], every level is box filtered and renormalized.
+/// Optionally the variance of the normals in every footprint is returned as well, see [`MipChain::level_variance()`].
+pub fn generate_normal_map_mip_chain(
+    src: &Image<'_, NormalMapFormat>,
+    options: &NormalMapMipOptions,
+) -> MipChain<NormalMapFormat> {
+    let format = src.format;
+    let pixel_size = format.pixel_size_in_bytes();
+    let levels = mip_levels(src.width, src.height, pixel_size);
+    let mut data = allocate_with_base_level(src, &levels);
+    // The base level has no variance, so it is left at 0.
+    let mut variance = if options.output_variance {
+        vec![0.0; data.len() / pixel_size]
+    } else {
+        Vec::new()
+    };
+
+    let mut base_normals = vec![0.0f32; levels[0].width as usize * levels[0].height as usize * 3];
+    unsafe {
+        ispc::decode_normal_map(
+            &src.ispc_representation(),
+            ispc::NormalMapFormat::from(format),
+            base_normals.as_mut_ptr(),
+        );
+    }
+    let mut previous_normals = base_normals.clone();
+
+    for (index, level) in levels.iter().enumerate().skip(1) {
+        let (src_normals, src_level) = match options.source {
+            MipSource::BaseImage => (&base_normals, &levels[0]),
+            MipSource::PreviousLevel => (&previous_normals, &levels[index - 1]),
+        };
+
+        let mut averages = vec![0.0f32; level.width as usize * level.height as usize * 3];
+        unsafe {
+            ispc::average_normals(
+                src_level.width,
+                src_level.height,
+                src_normals.as_ptr(),
+                level.width,
+                level.height,
+                averages.as_mut_ptr(),
+            );
+        }
+
+        let level_variance = if options.output_variance {
+            variance[level.offset_in_bytes / pixel_size..].as_mut_ptr()
+        } else {
+            std::ptr::null_mut()
+        };
+        let mut dst = ImageMut::new(
+            &mut data[level.offset_in_bytes..level.offset_in_bytes + level.size_in_bytes],
+            level.width,
+            level.height,
+            format,
+        );
+        unsafe {
+            ispc::write_normals(
+                averages.as_ptr(),
+                &mut dst.ispc_representation(),
+                ispc::NormalMapFormat::from(format),
+                level_variance,
+            );
+        }
+
+        previous_normals = averages;
+    }
+
+    MipChain {
+        data,
+        levels,
+        format,
+        variance,
+    }
+}
@@ -0,0 +1,70 @@
+//! Separable resampling passes which filter in floating point.
+//!
+//! The ISPC kernels accumulate along the width into a `src_height` x `dst_width` intermediate buffer of floats
+//! and then accumulate along the height. Only the final write quantizes the values.
+
+use crate::{
+    ispc, weights::Weights, AlbedoFormat, DownsampleOptions, Image, ImageMut, ImagePixelFormat,
+};
+
+/// Resamples `src` to the dimensions of `dst` with the ISPC kernel for the number of channels of `src`.
+/// `dst` has to have the same format as `src`. `scratch_space` is resized as needed and can be reused between calls.
+pub(crate) fn resample(
+    src: &Image<'_, AlbedoFormat>,
+    weights: &Weights,
+    options: &DownsampleOptions,
+    scratch_space: &mut Vec<f32>,
+    dst: &mut ImageMut<'_, AlbedoFormat>,
+) {
+    debug_assert_eq!(src.format, dst.format);
+    let num_channels = src.format.num_channel_in_memory();
+    let pixel_format = ispc::PixelFormat::from(src.format);
+
+    // The horizontal pass writes a src_height * target_width intermediate buffer of floats.
+    // Every value is overwritten, so there is no need to clear a previously used buffer.
+    scratch_space.resize(src.height as usize * dst.width as usize * num_channels, 0.0);
+
+    let horizontal_weights = weights.horizontal_weights().ispc_representation();
+    let vertical_weights = weights.vertical_weights().ispc_representation();
+    let mut ctx = ispc::DownsamplingContext {
+        weights: ispc::SampleWeights {
+            vertical_weights: &vertical_weights,
+            horizontal_weights: &horizontal_weights,
+        },
+        scratch_space: scratch_space.as_mut_ptr(),
+        clamp_to_footprint: options.clamp_to_footprint,
+        // Every float format has at least 3 channels.
+        suppress_hdr_ringing: options.suppress_hdr_ringing && src.format.is_float(),
+    };
+
+    // The layouts of both images were validated against their buffers when they were created,
+    // and the weights were calculated for their dimensions, so the kernels stay inside of the buffers.
+    unsafe {
+        match num_channels {
+            1 => ispc::resample_with_cached_weights_1(
+                &src.ispc_representation(),
+                &mut dst.ispc_representation(),
+                pixel_format,
+                &mut ctx,
+            ),
+            2 => ispc::resample_with_cached_weights_2(
+                &src.ispc_representation(),
+                &mut dst.ispc_representation(),
+                pixel_format,
+                &mut ctx,
+            ),
+            3 => ispc::resample_with_cached_weights_3(
+                &src.ispc_representation(),
+                &mut dst.ispc_representation(),
+                pixel_format,
+                &mut ctx,
+            ),
+            _ => ispc::resample_with_cached_weights_4(
+                &src.ispc_representation(),
+                &mut dst.ispc_representation(),
+                pixel_format,
+                &mut ctx,
+            ),
+        }
+    }
+}
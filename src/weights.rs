@@ -0,0 +1,204 @@
+//! Precomputed filter weights for the separable resampling passes.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+};
+
+use crate::{
+    ispc::{self, CachedWeight},
+    AxisFilter, DownsampleError, Filter, ResampleFilter,
+};
+
+/// Calculates the weights of the built-in filter `filter`. Lanczos weights are calculated by the ISPC kernel.
+pub(crate) fn calculate_axis_weights(
+    src: u32,
+    target: u32,
+    filter: &AxisFilter,
+) -> Result<WeightCollection, DownsampleError> {
+    let Filter::Lanczos { lobes } = filter.filter else {
+        return calculate_weights(src, target, filter);
+    };
+
+    calculate_weight_lines(
+        src,
+        target,
+        filter.support(),
+        |image_scale, dimensions, weights| {
+            unsafe {
+                ispc::calculate_weights_lanczos(
+                    image_scale,
+                    filter.filter_scale,
+                    lobes,
+                    dimensions,
+                    weights.as_mut_ptr(),
+                );
+            }
+            // The kernel normalizes the weights without checking their sum, see `calculate_filter_weights()`.
+            if weights.iter().all(|weight| weight.is_finite()) {
+                Ok(())
+            } else {
+                Err(DownsampleError::DegenerateFilter)
+            }
+        },
+    )
+}
+
+/// Calculates the weights of `filter`, which is sampled at every source pixel in the footprint of a target pixel.
+pub(crate) fn calculate_weights(
+    src: u32,
+    target: u32,
+    filter: &(impl ResampleFilter + ?Sized),
+) -> Result<WeightCollection, DownsampleError> {
+    calculate_weight_lines(
+        src,
+        target,
+        filter.support(),
+        |image_scale, dimensions, weights| {
+            calculate_filter_weights(|t| filter.evaluate(t), image_scale, dimensions, weights)
+        },
+    )
+}
+
+/// Calculates the weight lines for a filter with a radius of `support` target pixels.
+/// `calculate_line` fills the weights of every distinct line, given the image scale and the dimensions of the line.
+fn calculate_weight_lines(
+    src: u32,
+    target: u32,
+    support: f32,
+    calculate_line: impl Fn(f32, &ispc::WeightDimensions, &mut [f32]) -> Result<(), DownsampleError>,
+) -> Result<WeightCollection, DownsampleError> {
+    assert!(
+        src >= target,
+        "Trying to use downsampler to upsample or perform an operation which will cause no changes"
+    );
+    // Every line of weights is based on the start and end of the line, and its "center" which has the biggest weight.
+    // These weight lines follow a pattern, so we can skip calculating some of them by caching all different line we get.
+    // For that purpose, we first determine the variables which define the line.
+    let image_scale = src as f32 / target as f32;
+    let mut variables = vec![ispc::WeightDimensions::default(); target as usize];
+
+    unsafe {
+        ispc::calculate_weight_dimensions(support, src, target, variables.as_mut_ptr());
+    }
+
+    let mut lines = Vec::with_capacity(target as usize);
+    let mut coefficients = Vec::new();
+
+    // We cache the offsets of the weights in a map so that we can reuse them as we need.
+    // Half of the total number of weights seems like a good starting point to avoid unnecessary copies when resizing.
+    let mut reuse_heap = HashMap::<_, u32>::with_capacity(target as usize / 2);
+
+    for v in variables.iter() {
+        // The lines are clamped to the source, which the resampling passes rely on to index the source rows.
+        assert!(
+            0.0 <= v.src_start && v.src_start <= v.src_end && v.src_end < src as f32,
+            "Weight line {}..={} is outside of the source of {src} pixels",
+            v.src_start,
+            v.src_end
+        );
+        let coefficient_count = (v.src_end - v.src_start + 1.0) as u32;
+        // The unique values that define a collection of cached weights are how many pixels it includes and the distance from its start to its center.
+        // We use them to create a key based on which we reuse ones we've calculated previously.
+        let reuse_key = (
+            coefficient_count,
+            (v.src_center - v.src_start).to_ne_bytes(),
+        );
+
+        // If there is already a weight line calculated for that key, we point to its coefficients.
+        // If there isn't, we calculate the weights, append them to the coefficients and add them to the reuse heap.
+        let offset = match reuse_heap.entry(reuse_key) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                let offset = coefficients.len();
+                coefficients.resize(offset + coefficient_count as usize, 0.0);
+                calculate_line(image_scale, v, &mut coefficients[offset..])?;
+                *entry.insert(offset as u32)
+            }
+        };
+
+        lines.push(CachedWeight {
+            start: v.src_start as u32,
+            offset,
+            count: coefficient_count,
+        });
+    }
+
+    Ok(WeightCollection {
+        lines,
+        coefficients,
+    })
+}
+
+/// Evaluates `kernel` for every pixel of the line described by `dimensions`, and normalizes the weights such that their sum is 1.
+///
+/// Returns [`DownsampleError::DegenerateFilter`] if the weights cannot be normalized.
+fn calculate_filter_weights(
+    kernel: impl Fn(f32) -> f32,
+    image_scale: f32,
+    dimensions: &ispc::WeightDimensions,
+    weights: &mut [f32],
+) -> Result<(), DownsampleError> {
+    for (i, weight) in weights.iter_mut().enumerate() {
+        let t = (dimensions.src_start + i as f32 - dimensions.src_center) / image_scale;
+        *weight = kernel(t);
+    }
+
+    let sum = weights.iter().sum::<f32>();
+    // A kernel that is narrower than the distance between the source pixels can miss all of them,
+    // which would otherwise produce NaN weights.
+    if sum == 0.0 || !sum.is_finite() {
+        return Err(DownsampleError::DegenerateFilter);
+    }
+    for weight in weights.iter_mut() {
+        *weight /= sum;
+    }
+    Ok(())
+}
+
+/// All weight lines for a single axis, indexed by the target pixel along that axis.
+///
+/// The coefficients of all lines are stored in a single buffer, so the collection can be shared between threads.
+#[derive(Debug)]
+pub(crate) struct WeightCollection {
+    lines: Vec<CachedWeight>,
+    coefficients: Vec<f32>,
+}
+
+impl WeightCollection {
+    /// Returns the representation of this collection which is passed to the ISPC kernels.
+    /// It points into the buffers of this collection, so it must not outlive it.
+    pub(crate) fn ispc_representation(&self) -> ispc::WeightCollection {
+        ispc::WeightCollection {
+            lines: self.lines.as_ptr(),
+            coefficients: self.coefficients.as_ptr(),
+        }
+    }
+}
+
+/// The weights for both axes of a resampling operation.
+#[derive(Debug, Clone)]
+pub(crate) struct Weights {
+    horizontal_weights: Arc<WeightCollection>,
+    vertical_weights: Arc<WeightCollection>,
+}
+
+impl Weights {
+    pub(crate) fn new(
+        horizontal_weights: Arc<WeightCollection>,
+        vertical_weights: Arc<WeightCollection>,
+    ) -> Self {
+        Self {
+            horizontal_weights,
+            vertical_weights,
+        }
+    }
+
+    pub(crate) fn horizontal_weights(&self) -> &WeightCollection {
+        &self.horizontal_weights
+    }
+
+    pub(crate) fn vertical_weights(&self) -> &WeightCollection {
+        &self.vertical_weights
+    }
+}
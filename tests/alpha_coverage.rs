@@ -0,0 +1,123 @@
+use ispc_downsampler::{
+    downsample, scale_alpha_to_original_coverage, scale_alpha_to_original_coverage_in_channel,
+    try_scale_alpha_to_original_coverage_in_channel, AlbedoFormat, DownsampleError, Image,
+};
+
+/// Alpha of thin branches, which lose coverage when downsampled.
+fn alpha(width: usize, height: usize) -> Vec<u8> {
+    (0..width * height)
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+            if x % 8 == 0 || (x + y) % 11 == 0 {
+                255
+            } else {
+                0
+            }
+        })
+        .collect()
+}
+
+/// Downsamples `pixels` to 16x16 and rescales the alpha in `alpha_channel`.
+fn downsample_and_rescale(
+    pixels: &[u8],
+    format: AlbedoFormat,
+    pixel_stride: usize,
+    alpha_channel: usize,
+) -> Vec<u8> {
+    let image = Image::new_with_pixel_stride(pixels, 64, 64, format, pixel_stride);
+    let downsampled = downsample(&image, 16, 16);
+    scale_alpha_to_original_coverage_in_channel(
+        &image,
+        &Image::new(&downsampled, 16, 16, format),
+        alpha_channel,
+        Some(0.5),
+    )
+}
+
+#[test]
+fn single_channel_mask_matches_rgba_alpha() {
+    let mask = alpha(64, 64);
+    let rgba = mask
+        .iter()
+        .flat_map(|&a| [30, 90, 30, a])
+        .collect::<Vec<_>>();
+
+    let rescaled_rgba = downsample_and_rescale(&rgba, AlbedoFormat::Rgba8Unorm, 4, 3);
+    let rescaled_mask = {
+        let image = Image::new(&mask, 64, 64, AlbedoFormat::R8Unorm);
+        let downsampled = downsample(&image, 16, 16);
+        scale_alpha_to_original_coverage(
+            &image,
+            &Image::new(&downsampled, 16, 16, AlbedoFormat::R8Unorm),
+            Some(0.5),
+        )
+    };
+
+    let rgba_alpha = rescaled_rgba.chunks_exact(4).map(|pixel| pixel[3]);
+    assert!(rgba_alpha.eq(rescaled_mask.iter().copied()));
+    // The rescaling has to make a difference for this test to be meaningful.
+    let image = Image::new(&mask, 64, 64, AlbedoFormat::R8Unorm);
+    assert_ne!(rescaled_mask, downsample(&image, 16, 16));
+}
+
+#[test]
+fn alpha_can_be_in_any_channel() {
+    let mask = alpha(64, 64);
+    let alpha_last = mask
+        .iter()
+        .flat_map(|&a| [30, 90, 30, a])
+        .collect::<Vec<_>>();
+    let alpha_first = mask
+        .iter()
+        .flat_map(|&a| [a, 30, 90, 30])
+        .collect::<Vec<_>>();
+
+    let rescaled_last = downsample_and_rescale(&alpha_last, AlbedoFormat::Rgba8Unorm, 4, 3);
+    let rescaled_first = downsample_and_rescale(&alpha_first, AlbedoFormat::Rgba8Unorm, 4, 0);
+    for (last, first) in rescaled_last
+        .chunks_exact(4)
+        .zip(rescaled_first.chunks_exact(4))
+    {
+        assert_eq!(last, [first[3], first[2], first[1], first[0]]);
+    }
+
+    // Alpha in the second channel of a two channel format, which has no default alpha channel.
+    let rg = mask.iter().flat_map(|&a| [90, a]).collect::<Vec<_>>();
+    let rescaled_rg = downsample_and_rescale(&rg, AlbedoFormat::Rg8Unorm, 2, 1);
+    assert!(rescaled_rg
+        .chunks_exact(2)
+        .map(|pixel| pixel[1])
+        .eq(rescaled_last.chunks_exact(4).map(|pixel| pixel[3])));
+}
+
+#[test]
+fn strided_source_matches_packed_source() {
+    let mask = alpha(64, 64);
+    let packed = mask
+        .iter()
+        .flat_map(|&a| [30, 90, 30, a])
+        .collect::<Vec<_>>();
+    let strided = mask
+        .iter()
+        .flat_map(|&a| [30, 90, 30, a, 7, 7, 7, 7])
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        downsample_and_rescale(&strided, AlbedoFormat::Rgba8Unorm, 8, 3),
+        downsample_and_rescale(&packed, AlbedoFormat::Rgba8Unorm, 4, 3)
+    );
+}
+
+#[test]
+fn alpha_channel_has_to_exist() {
+    let pixels = vec![0u8; 8 * 8 * 3];
+    let image = Image::new(&pixels, 8, 8, AlbedoFormat::Rgb8Unorm);
+
+    assert_eq!(
+        try_scale_alpha_to_original_coverage_in_channel(&image, &image, 3, None),
+        Err(DownsampleError::ChannelOutOfRange {
+            channel: 3,
+            num_channels: 3
+        })
+    );
+}
@@ -0,0 +1,51 @@
+use ispc_downsampler::{downsample, AlbedoFormat, Image};
+
+/// Returns an RGBA image with different content in every channel.
+fn rgba(width: usize, height: usize) -> Vec<u8> {
+    (0..width * height)
+        .flat_map(|i| {
+            let (x, y) = (i % width, i / width);
+            [
+                (x * 9 + y * 3) as u8,
+                ((x * y) % 251) as u8,
+                (x ^ y) as u8 * 4,
+                (255 - x * 4) as u8,
+            ]
+        })
+        .collect()
+}
+
+#[test]
+fn fewer_channels_match_rgba() {
+    let pixels = rgba(48, 40);
+
+    for (rgba_format, formats) in [
+        (
+            AlbedoFormat::Rgba8Unorm,
+            [(AlbedoFormat::R8Unorm, 1), (AlbedoFormat::Rg8Unorm, 2)],
+        ),
+        (
+            AlbedoFormat::Rgba8Snorm,
+            [(AlbedoFormat::R8Snorm, 1), (AlbedoFormat::Rg8Snorm, 2)],
+        ),
+    ] {
+        let expected = downsample(&Image::new(&pixels, 48, 40, rgba_format), 16, 10);
+
+        for (format, channels) in formats {
+            let src = pixels
+                .chunks_exact(4)
+                .flat_map(|pixel| &pixel[..channels])
+                .copied()
+                .collect::<Vec<_>>();
+            let downsampled = downsample(&Image::new(&src, 48, 40, format), 16, 10);
+
+            assert_eq!(downsampled.len(), 16 * 10 * channels, "{format:?}");
+            let expected = expected
+                .chunks_exact(4)
+                .flat_map(|pixel| &pixel[..channels])
+                .copied()
+                .collect::<Vec<_>>();
+            assert_eq!(downsampled, expected, "{format:?}");
+        }
+    }
+}
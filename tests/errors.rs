@@ -0,0 +1,96 @@
+use ispc_downsampler::{
+    try_downsample, try_downsample_with_custom_scale, try_downsample_with_options,
+    try_scale_alpha_to_original_coverage, AlbedoFormat, AxisFilter, DownsampleError,
+    DownsampleOptions, Downsampler, Filter, Image,
+};
+
+#[test]
+fn invalid_target_dimensions_are_errors() {
+    let pixels = vec![0u8; 16 * 16 * 4];
+    let image = Image::new(&pixels, 16, 16, AlbedoFormat::Rgba8Unorm);
+
+    assert_eq!(
+        try_downsample(&image, 16, 16),
+        Err(DownsampleError::SameResolution {
+            width: 16,
+            height: 16
+        })
+    );
+    assert_eq!(
+        try_downsample(&image, 8, 32),
+        Err(DownsampleError::Upsampling {
+            src_width: 16,
+            src_height: 16,
+            target_width: 8,
+            target_height: 32
+        })
+    );
+    assert_eq!(
+        try_downsample(&image, 0, 8),
+        Err(DownsampleError::EmptyImage {
+            width: 0,
+            height: 8
+        })
+    );
+}
+
+#[test]
+fn invalid_filter_scale_is_an_error() {
+    let pixels = vec![0u8; 16 * 16 * 4];
+    let image = Image::new(&pixels, 16, 16, AlbedoFormat::Rgba8Unorm);
+
+    for filter_scale in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        let options = DownsampleOptions::with_filter(AxisFilter {
+            filter: Filter::Box,
+            filter_scale,
+        });
+        assert!(matches!(
+            try_downsample_with_options(&image, 8, 8, &options),
+            Err(DownsampleError::InvalidFilterScale(_))
+        ));
+        // The custom scale is the number of lobes of the Lanczos filter.
+        assert!(matches!(
+            try_downsample_with_custom_scale(&image, 8, 8, filter_scale),
+            Err(DownsampleError::InvalidLanczosLobes(_))
+        ));
+    }
+}
+
+#[test]
+fn alpha_coverage_requires_alpha_channel() {
+    let pixels = vec![0u8; 16 * 16 * 3];
+    let image = Image::new(&pixels, 16, 16, AlbedoFormat::Rgb8Unorm);
+    let downsampled_pixels = vec![0u8; 8 * 8 * 3];
+    let downsampled = Image::new(&downsampled_pixels, 8, 8, AlbedoFormat::Rgb8Unorm);
+
+    assert_eq!(
+        try_scale_alpha_to_original_coverage(&image, &downsampled, None),
+        Err(DownsampleError::NoAlphaChannel)
+    );
+}
+
+#[test]
+fn downsampler_rejects_other_dimensions() {
+    let pixels = vec![0u8; 32 * 32 * 4];
+    let image = Image::new(&pixels, 32, 32, AlbedoFormat::Rgba8Unorm);
+    let mut downsampler = Downsampler::try_new(16, 16, 8, 8, &DownsampleOptions::default())
+        .expect("valid dimensions");
+
+    assert_eq!(
+        downsampler.try_downsample(&image),
+        Err(DownsampleError::DimensionMismatch {
+            expected_width: 16,
+            expected_height: 16,
+            width: 32,
+            height: 32
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "same resolution")]
+fn panicking_wrapper_reports_error() {
+    let pixels = vec![0u8; 16 * 16 * 4];
+    let image = Image::new(&pixels, 16, 16, AlbedoFormat::Rgba8Unorm);
+    ispc_downsampler::downsample(&image, 16, 16);
+}
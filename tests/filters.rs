@@ -0,0 +1,407 @@
+use ispc_downsampler::{
+    downsample_with_filter, downsample_with_filter_into, downsample_with_options,
+    generate_mip_chain_with_filter, try_downsample_with_filter, try_downsample_with_options,
+    try_generate_mip_chain_with_filter, AlbedoFormat, AxisFilter, DownsampleError,
+    DownsampleOptions, Downsampler, Filter, Image, ImageMut, MipOptions, ResampleFilter,
+};
+
+const LANCZOS3: Filter = Filter::Lanczos { lobes: 3.0 };
+
+const FILTERS: [Filter; 7] = [
+    Filter::Box,
+    Filter::Triangle,
+    Filter::Mitchell,
+    Filter::CatmullRom,
+    Filter::Gaussian,
+    Filter::Kaiser,
+    LANCZOS3,
+];
+
+fn options(filter: Filter) -> DownsampleOptions {
+    DownsampleOptions::with_filter(filter)
+}
+
+/// Returns a `width` x `height` single precision float image where every channel of a pixel has `value(x, y)`.
+fn float_image(width: u32, height: u32, value: impl Fn(u32, u32) -> f32) -> Vec<u8> {
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|(x, y)| [value(x, y); 4])
+        .flat_map(f32::to_le_bytes)
+        .collect()
+}
+
+fn red_channel(pixels: &[u8]) -> Vec<f32> {
+    pixels
+        .chunks_exact(16)
+        .map(|pixel| f32::from_le_bytes(pixel[..4].try_into().unwrap()))
+        .collect()
+}
+
+#[test]
+fn every_filter_preserves_constant_images() {
+    let pixels = [90u8, 150, 30, 255].repeat(48 * 40);
+    let image = Image::new(&pixels, 48, 40, AlbedoFormat::Rgba8Unorm);
+
+    for filter in FILTERS {
+        let downsampled = downsample_with_options(&image, 12, 13, &options(filter));
+        assert!(
+            downsampled
+                .chunks_exact(4)
+                .all(|pixel| pixel == [90, 150, 30, 255]),
+            "{filter:?} changed a constant image"
+        );
+    }
+}
+
+#[test]
+fn box_filter_averages_blocks() {
+    let pixels = float_image(8, 8, |x, y| (x * 8 + y) as f32);
+    let image = Image::new(&pixels, 8, 8, AlbedoFormat::Rgba32Float);
+
+    let downsampled = red_channel(&downsample_with_options(
+        &image,
+        4,
+        4,
+        &options(Filter::Box),
+    ));
+    for y in 0..4 {
+        for x in 0..4 {
+            let expected = ((2 * x) * 8 + 2 * y) as f32 + 4.5;
+            assert!((downsampled[y * 4 + x] - expected).abs() < 1e-4);
+        }
+    }
+}
+
+#[test]
+fn only_negative_lobes_overshoot_edges() {
+    let pixels = float_image(64, 64, |x, _| if x < 29 { 0.0 } else { 1.0 });
+    let image = Image::new(&pixels, 64, 64, AlbedoFormat::Rgba32Float);
+
+    for filter in FILTERS {
+        let downsampled = red_channel(&downsample_with_options(&image, 16, 16, &options(filter)));
+        let overshoots = downsampled
+            .iter()
+            .any(|&v| !(-1e-5..=1.0 + 1e-5).contains(&v));
+        let has_negative_lobes = matches!(
+            filter,
+            Filter::Mitchell | Filter::CatmullRom | Filter::Kaiser | Filter::Lanczos { .. }
+        );
+        assert_eq!(overshoots, has_negative_lobes, "{filter:?}");
+    }
+}
+
+#[test]
+fn downsampler_uses_filter() {
+    let pixels = float_image(32, 32, |x, y| ((x * 3 + y * 5) % 7) as f32);
+    let image = Image::new(&pixels, 32, 32, AlbedoFormat::Rgba32Float);
+
+    let lanczos = downsample_with_options(&image, 8, 8, &options(LANCZOS3));
+    for filter in FILTERS {
+        let expected = downsample_with_options(&image, 8, 8, &options(filter));
+        let mut downsampler = Downsampler::new(32, 32, 8, 8, &options(filter));
+        assert_eq!(downsampler.downsample(&image), expected);
+        assert_eq!(filter == LANCZOS3, expected == lanczos);
+    }
+}
+
+/// Tent filter with a configurable support, which matches [`Filter::Triangle`] with a support of 1.
+struct Tent(f32);
+
+impl ResampleFilter for Tent {
+    fn support(&self) -> f32 {
+        self.0
+    }
+
+    fn evaluate(&self, t: f32) -> f32 {
+        (1.0 - t.abs() / self.0).max(0.0)
+    }
+}
+
+#[test]
+fn custom_filter_matches_builtin_filter() {
+    let pixels = float_image(40, 24, |x, y| ((x * 3 + y * 5) % 7) as f32);
+    let image = Image::new(&pixels, 40, 24, AlbedoFormat::Rgba32Float);
+
+    let expected = downsample_with_options(&image, 12, 9, &options(Filter::Triangle));
+    let mut downsampler =
+        Downsampler::new_with_filter(40, 24, 12, 9, &Tent(1.0), &DownsampleOptions::default());
+    let downsampled = downsampler.downsample(&image);
+
+    for (a, b) in red_channel(&downsampled).iter().zip(red_channel(&expected)) {
+        assert!((a - b).abs() < 1e-5, "{a} != {b}");
+    }
+}
+
+#[test]
+fn custom_filter_support_changes_result() {
+    let pixels = float_image(32, 32, |x, y| ((x * 3 + y * 5) % 7) as f32);
+    let image = Image::new(&pixels, 32, 32, AlbedoFormat::Rgba32Float);
+    let options = DownsampleOptions::default();
+
+    let narrow =
+        Downsampler::new_with_filter(32, 32, 8, 8, &Tent(1.0), &options).downsample(&image);
+    let wide = Downsampler::new_with_filter(32, 32, 8, 8, &Tent(2.5), &options).downsample(&image);
+    assert_ne!(narrow, wide);
+}
+
+#[test]
+fn one_shot_custom_filter_matches_downsampler() {
+    let pixels = float_image(40, 24, |x, y| ((x * 3 + y * 5) % 7) as f32);
+    let image = Image::new(&pixels, 40, 24, AlbedoFormat::Rgba32Float);
+    let options = DownsampleOptions::default();
+
+    let expected =
+        Downsampler::new_with_filter(40, 24, 12, 9, &Tent(2.0), &options).downsample(&image);
+    assert_eq!(
+        downsample_with_filter(&image, 12, 9, &Tent(2.0), &options),
+        expected
+    );
+
+    let mut pixels = vec![0; expected.len()];
+    let mut dst = ImageMut::new(&mut pixels, 12, 9, AlbedoFormat::Rgba32Float);
+    downsample_with_filter_into(&image, &mut dst, &Tent(2.0), &options);
+    assert_eq!(pixels, expected);
+
+    assert!(matches!(
+        try_downsample_with_filter(&image, 12, 9, &Tent(0.0), &options),
+        Err(DownsampleError::InvalidFilterSupport(_))
+    ));
+}
+
+#[test]
+fn mip_chain_with_custom_filter() {
+    let pixels = float_image(16, 8, |x, y| ((x * 3 + y * 5) % 7) as f32);
+    let image = Image::new(&pixels, 16, 8, AlbedoFormat::Rgba32Float);
+    let options = MipOptions::default();
+
+    let chain = generate_mip_chain_with_filter(&image, &Tent(2.0), &options);
+    assert_eq!(chain.levels().len(), 5);
+    assert_eq!(
+        chain.level_data(1),
+        downsample_with_filter(&image, 8, 4, &Tent(2.0), &options.downsample_options)
+    );
+
+    assert!(matches!(
+        try_generate_mip_chain_with_filter(&image, &Tent(f32::NAN), &options),
+        Err(DownsampleError::InvalidFilterSupport(_))
+    ));
+}
+
+/// Unit struct filters are zero-sized, so references to different ones can share their address.
+struct Wide;
+
+impl ResampleFilter for Wide {
+    fn support(&self) -> f32 {
+        2.5
+    }
+
+    fn evaluate(&self, t: f32) -> f32 {
+        Tent(2.5).evaluate(t)
+    }
+}
+
+struct Narrow;
+
+impl ResampleFilter for Narrow {
+    fn support(&self) -> f32 {
+        1.0
+    }
+
+    fn evaluate(&self, t: f32) -> f32 {
+        Tent(1.0).evaluate(t)
+    }
+}
+
+#[test]
+fn different_unit_struct_filters_do_not_share_weights() {
+    let pixels = float_image(32, 32, |x, y| ((x * 3 + y * 5) % 7) as f32);
+    let image = Image::new(&pixels, 32, 32, AlbedoFormat::Rgba32Float);
+    let options = DownsampleOptions::default();
+
+    let wide = Downsampler::new_with_filter(32, 32, 8, 8, &Wide, &options).downsample(&image);
+    let wide_and_narrow =
+        Downsampler::new_with_filters(32, 32, 8, 8, &Wide, &Narrow, &options).downsample(&image);
+    assert_ne!(wide, wide_and_narrow);
+}
+
+#[test]
+fn invalid_custom_filter_support_is_an_error() {
+    let options = DownsampleOptions::default();
+    for support in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        assert!(matches!(
+            Downsampler::try_new_with_filter(16, 16, 8, 8, &Tent(support), &options),
+            Err(DownsampleError::InvalidFilterSupport(_))
+        ));
+    }
+    assert!(matches!(
+        Downsampler::try_new_with_filter(8, 8, 16, 16, &Tent(1.0), &options),
+        Err(DownsampleError::Upsampling { .. })
+    ));
+}
+
+#[test]
+fn clamp_to_footprint_removes_overshoot() {
+    // Edges along both axes, so both passes overshoot without the clamp.
+    let pixels = float_image(64, 64, |x, y| if x < 29 && y < 35 { 0.0 } else { 1.0 });
+    let image = Image::new(&pixels, 64, 64, AlbedoFormat::Rgba32Float);
+
+    for filter in FILTERS {
+        let clamped_options = DownsampleOptions {
+            clamp_to_footprint: true,
+            ..options(filter)
+        };
+        let clamped = red_channel(&downsample_with_options(&image, 16, 16, &clamped_options));
+        assert!(
+            clamped.iter().all(|v| (0.0..=1.0).contains(v)),
+            "{filter:?}"
+        );
+
+        // Pixels whose footprint does not reach the edges keep the exact value of the flat regions.
+        assert_eq!(clamped[0], 0.0, "{filter:?}");
+        assert_eq!(clamped[15 * 16 + 15], 1.0, "{filter:?}");
+
+        // Filters without negative lobes stay inside of the range of their footprint on their own,
+        // apart from rounding errors.
+        if matches!(filter, Filter::Box | Filter::Triangle | Filter::Gaussian) {
+            let unclamped = red_channel(&downsample_with_options(&image, 16, 16, &options(filter)));
+            for (clamped, unclamped) in clamped.iter().zip(unclamped) {
+                assert!((clamped - unclamped).abs() < 1e-5, "{filter:?}");
+            }
+        }
+    }
+}
+
+/// Returns the `width` x `height` values of `values` with rows and columns swapped.
+fn transpose(values: &[f32], width: usize, height: usize) -> Vec<f32> {
+    (0..width)
+        .flat_map(|x| (0..height).map(move |y| values[y * width + x]))
+        .collect()
+}
+
+#[test]
+fn axis_filters_are_independent() {
+    let value = |x: u32, y: u32| ((x * 3 + y * 5) % 7) as f32 + (x as f32 * 0.3).sin();
+    let pixels = float_image(48, 40, value);
+    let transposed_pixels = float_image(40, 48, |x, y| value(y, x));
+    let image = Image::new(&pixels, 48, 40, AlbedoFormat::Rgba32Float);
+    let transposed_image = Image::new(&transposed_pixels, 40, 48, AlbedoFormat::Rgba32Float);
+
+    let sharp = AxisFilter::from(Filter::Lanczos { lobes: 4.0 });
+    let soft = AxisFilter {
+        filter: Filter::Gaussian,
+        filter_scale: 1.5,
+    };
+    let options = DownsampleOptions {
+        horizontal: sharp,
+        vertical: soft,
+        ..Default::default()
+    };
+    let transposed_options = DownsampleOptions {
+        horizontal: soft,
+        vertical: sharp,
+        ..Default::default()
+    };
+
+    let downsampled = red_channel(&downsample_with_options(&image, 12, 10, &options));
+    let transposed = red_channel(&downsample_with_options(
+        &transposed_image,
+        10,
+        12,
+        &transposed_options,
+    ));
+    for (a, b) in downsampled.iter().zip(transpose(&transposed, 10, 12)) {
+        assert!((a - b).abs() < 1e-4, "{a} != {b}");
+    }
+
+    assert_ne!(
+        downsampled,
+        red_channel(&downsample_with_options(
+            &image,
+            12,
+            10,
+            &DownsampleOptions::with_filter(sharp)
+        ))
+    );
+    assert_ne!(
+        downsampled,
+        red_channel(&downsample_with_options(
+            &image,
+            12,
+            10,
+            &DownsampleOptions::with_filter(soft)
+        ))
+    );
+}
+
+#[test]
+fn custom_axis_filters_match_builtin_filters() {
+    let pixels = float_image(32, 32, |x, y| ((x * 3 + y * 5) % 7) as f32);
+    let image = Image::new(&pixels, 32, 32, AlbedoFormat::Rgba32Float);
+
+    let options = DownsampleOptions {
+        horizontal: AxisFilter::from(Filter::Triangle),
+        vertical: AxisFilter {
+            filter: Filter::Triangle,
+            filter_scale: 2.0,
+        },
+        ..Default::default()
+    };
+    let expected = downsample_with_options(&image, 8, 8, &options);
+    let downsampled = Downsampler::new_with_filters(
+        32,
+        32,
+        8,
+        8,
+        &Tent(1.0),
+        &Tent(2.0),
+        &DownsampleOptions::default(),
+    )
+    .downsample(&image);
+
+    for (a, b) in red_channel(&downsampled).iter().zip(red_channel(&expected)) {
+        assert!((a - b).abs() < 1e-5, "{a} != {b}");
+    }
+}
+
+#[test]
+fn degenerate_filters_are_errors() {
+    // At a ratio of 2 the source pixels are 0.25 target pixels away from every center, so these filters miss all of them.
+    let pixels = [200u8; 4 * 4 * 4];
+    let image = Image::new(&pixels, 4, 4, AlbedoFormat::Rgba8Unorm);
+
+    for filter in [
+        AxisFilter {
+            filter: Filter::Box,
+            filter_scale: 0.25,
+        },
+        AxisFilter {
+            filter: Filter::Triangle,
+            filter_scale: 0.2,
+        },
+        AxisFilter::from(Filter::Lanczos { lobes: 0.2 }),
+    ] {
+        assert_eq!(
+            try_downsample_with_options(&image, 2, 2, &DownsampleOptions::with_filter(filter)),
+            Err(DownsampleError::DegenerateFilter),
+            "{filter:?}"
+        );
+    }
+
+    assert!(matches!(
+        Downsampler::try_new_with_filter(4, 4, 2, 2, &Tent(0.1), &DownsampleOptions::default()),
+        Err(DownsampleError::DegenerateFilter)
+    ));
+
+    // Only the axis with the degenerate filter matters.
+    let options = DownsampleOptions {
+        vertical: AxisFilter {
+            filter: Filter::Box,
+            filter_scale: 0.25,
+        },
+        ..Default::default()
+    };
+    assert_eq!(
+        try_downsample_with_options(&image, 2, 2, &options),
+        Err(DownsampleError::DegenerateFilter)
+    );
+}
@@ -0,0 +1,113 @@
+use half::f16;
+use ispc_downsampler::{
+    downsample, downsample_with_options, scale_alpha_to_original_coverage, AlbedoFormat,
+    DownsampleOptions, Image,
+};
+
+fn f32_image(width: u32, height: u32, color: impl Fn(u32, u32) -> [f32; 4]) -> Vec<u8> {
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|(x, y)| color(x, y))
+        .flat_map(f32::to_le_bytes)
+        .collect()
+}
+
+fn read_f32(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|value| f32::from_le_bytes(value.try_into().unwrap()))
+        .collect()
+}
+
+#[test]
+fn hdr_values_survive_round_trip() {
+    const COLOR: [f32; 4] = [4.0, 12.5, 100.0, 1.0];
+
+    let pixels = f32_image(16, 16, |_, _| COLOR);
+    let downsampled = downsample(
+        &Image::new(&pixels, 16, 16, AlbedoFormat::Rgba32Float),
+        8,
+        8,
+    );
+    for pixel in read_f32(&downsampled).chunks_exact(4) {
+        for (value, expected) in pixel.iter().zip(COLOR) {
+            assert!(
+                (value - expected).abs() < expected * 1e-5,
+                "{value} != {expected}"
+            );
+        }
+    }
+
+    let pixels = (0..16 * 16)
+        .flat_map(|_| COLOR.map(f16::from_f32))
+        .flat_map(f16::to_le_bytes)
+        .collect::<Vec<_>>();
+    let downsampled = downsample(
+        &Image::new(&pixels, 16, 16, AlbedoFormat::Rgba16Float),
+        8,
+        8,
+    );
+    for pixel in downsampled.chunks_exact(8) {
+        for (value, expected) in pixel.chunks_exact(2).zip(COLOR) {
+            let value = f16::from_le_bytes([value[0], value[1]]).to_f32();
+            assert!(
+                (value - expected).abs() < expected * 1e-3,
+                "{value} != {expected}"
+            );
+        }
+    }
+}
+
+#[test]
+fn suppress_hdr_ringing_removes_dark_halo() {
+    const BACKGROUND: f32 = 0.25;
+    // A single very bright texel, such as the sun in an environment map.
+    let pixels = f32_image(32, 32, |x, y| {
+        let value = if (x, y) == (15, 15) {
+            50000.0
+        } else {
+            BACKGROUND
+        };
+        [value, value, value, 1.0]
+    });
+    let image = Image::new(&pixels, 32, 32, AlbedoFormat::Rgba32Float);
+    let darkest = |options: &DownsampleOptions| {
+        read_f32(&downsample_with_options(&image, 8, 8, options))
+            .chunks_exact(4)
+            .map(|pixel| pixel[0])
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    // The negative lobes around the bright texel pull its neighbors far below the background, or even below 0.
+    assert!(darkest(&DownsampleOptions::default()) < 0.0);
+
+    let suppressed = DownsampleOptions {
+        suppress_hdr_ringing: true,
+        ..Default::default()
+    };
+    let darkest = darkest(&suppressed);
+    assert!(darkest > BACKGROUND * 0.9, "{darkest}");
+}
+
+#[test]
+fn rescaled_float_alpha_is_clamped() {
+    // The source is fully covered, while half of the downsampled pixels are below the cutoff,
+    // so the alpha has to be scaled up until those pass the cutoff, which pushes the other half past 1.
+    let pixels = f32_image(16, 16, |_, _| [0.5, 0.5, 0.5, 1.0]);
+    let downsampled = f32_image(8, 8, |x, y| {
+        let alpha = if (x + y) % 2 == 0 { 0.9 } else { 0.5 };
+        [0.5, 0.5, 0.5, alpha]
+    });
+    let rescaled = scale_alpha_to_original_coverage(
+        &Image::new(&pixels, 16, 16, AlbedoFormat::Rgba32Float),
+        &Image::new(&downsampled, 8, 8, AlbedoFormat::Rgba32Float),
+        Some(0.6),
+    );
+
+    let alpha = read_f32(&rescaled)
+        .chunks_exact(4)
+        .map(|pixel| pixel[3])
+        .collect::<Vec<_>>();
+    assert!(alpha.iter().all(|a| (0.0..=1.0).contains(a)), "{alpha:?}");
+    assert!(alpha.contains(&1.0));
+}
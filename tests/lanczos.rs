@@ -0,0 +1,193 @@
+use ispc_downsampler::{
+    downsample, downsample_with_custom_scale, downsample_with_options, AlbedoFormat, AxisFilter,
+    DownsampleOptions, Filter, Image,
+};
+
+/// Scalar Lanczos reference with `lobes` lobes, stretched by `filter_scale`, which follows the footprint of the crate's
+/// weight calculation, but filters in `f64` and only quantizes once at the very end.
+fn reference_weights(
+    src: u32,
+    target: u32,
+    lobes: f64,
+    filter_scale: f64,
+) -> Vec<(usize, Vec<f64>)> {
+    let ratio = src as f64 / target as f64;
+    let radius = (ratio * lobes * filter_scale).ceil();
+    let sinc = |x: f64| {
+        if x == 0.0 {
+            1.0
+        } else {
+            (x * std::f64::consts::PI).sin() / (x * std::f64::consts::PI)
+        }
+    };
+
+    (0..target)
+        .map(|pixel| {
+            let center = (pixel as f64 + 0.5) * ratio - 0.5;
+            let start = (center - radius).ceil().clamp(0.0, src as f64 - 1.0);
+            let end = (center + radius)
+                .floor()
+                .clamp(0.0, src as f64 - 1.0)
+                .max(start);
+            let mut weights = (start as usize..=end as usize)
+                .map(|i| {
+                    let t = ((i as f64 - center) / (ratio * filter_scale)).abs();
+                    if t < lobes {
+                        sinc(t) * sinc(t / lobes)
+                    } else {
+                        0.0
+                    }
+                })
+                .collect::<Vec<_>>();
+            let sum = weights.iter().sum::<f64>();
+            weights.iter_mut().for_each(|w| *w /= sum);
+            (start as usize, weights)
+        })
+        .collect()
+}
+
+fn reference_downsample(
+    pixels: &[f64],
+    width: u32,
+    height: u32,
+    target: u32,
+    lobes: f64,
+    filter_scale: f64,
+) -> Vec<f64> {
+    let horizontal = reference_weights(width, target, lobes, filter_scale);
+    let vertical = reference_weights(height, target, lobes, filter_scale);
+    let (width, target_size) = (width as usize, target as usize);
+
+    let intermediate = (0..height as usize)
+        .flat_map(|y| {
+            horizontal.iter().map(move |(start, weights)| {
+                weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| pixels[y * width + start + i] * w)
+                    .sum::<f64>()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    vertical
+        .iter()
+        .flat_map(|(start, weights)| {
+            let intermediate = &intermediate;
+            (0..target_size).map(move |x| {
+                weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| intermediate[(start + i) * target_size + x] * w)
+                    .sum::<f64>()
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn smooth_gradient_matches_float_reference() {
+    const SIZE: u32 = 128;
+    const TARGET: u32 = 32;
+
+    // A shallow gradient with a gentle wave, which bands visibly when the intermediate result is quantized.
+    let luminance = (0..SIZE * SIZE)
+        .map(|i| {
+            let (x, y) = ((i % SIZE) as f64, (i / SIZE) as f64);
+            (64.0 + x * 0.75 + 24.0 * (y * 0.15).sin()).round() as u8
+        })
+        .collect::<Vec<_>>();
+    let rgb = luminance
+        .iter()
+        .flat_map(|&l| [l, l, l])
+        .collect::<Vec<_>>();
+
+    let downsampled = downsample(
+        &Image::new(&rgb, SIZE, SIZE, AlbedoFormat::Rgb8Unorm),
+        TARGET,
+        TARGET,
+    );
+    let luminance = luminance.iter().map(|&l| l as f64).collect::<Vec<_>>();
+    let reference = reference_downsample(&luminance, SIZE, SIZE, TARGET, 3.0, 1.0);
+
+    let errors = downsampled
+        .chunks_exact(3)
+        .zip(&reference)
+        .map(|(pixel, reference)| (pixel[0] as f64 - reference.clamp(0.0, 255.0)).abs())
+        .collect::<Vec<_>>();
+
+    let max_error = errors.iter().cloned().fold(0.0, f64::max);
+    let mean_error = errors.iter().sum::<f64>() / errors.len() as f64;
+    // Quantizing only once means we can at most be off by the final rounding step.
+    assert!(max_error <= 0.5 + 1e-3, "max error {max_error}");
+    assert!(mean_error < 0.3, "mean error {mean_error}");
+}
+
+#[test]
+fn lobes_and_filter_scale_match_float_reference() {
+    // An even ratio, where the centers of the footprints fall between source pixels, an odd ratio, where they fall
+    // on source pixels, and non-integer ratios, where they move relative to the source pixels.
+    for (size, target) in [(64, 16), (60, 20), (100, 60), (61, 30), (45, 16)] {
+        let values = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) as f64, (i / size) as f64);
+                ((x * 0.9).sin() + (y * 0.4).cos() * 0.5 + x * y / 2048.0) * 4.0
+            })
+            .collect::<Vec<_>>();
+        let pixels = values
+            .iter()
+            .flat_map(|&v| [v as f32; 4])
+            .flat_map(f32::to_le_bytes)
+            .collect::<Vec<_>>();
+        let image = Image::new(&pixels, size, size, AlbedoFormat::Rgba32Float);
+
+        for lobes in [1.0, 2.0, 3.0, 4.0] {
+            for filter_scale in [0.75, 1.0, 1.5] {
+                let options = DownsampleOptions::with_filter(AxisFilter {
+                    filter: Filter::Lanczos { lobes },
+                    filter_scale,
+                });
+                let downsampled = downsample_with_options(&image, target, target, &options);
+                let reference = reference_downsample(
+                    &values,
+                    size,
+                    size,
+                    target,
+                    lobes as f64,
+                    filter_scale as f64,
+                );
+
+                for (pixel, reference) in downsampled.chunks_exact(16).zip(&reference) {
+                    let value = f32::from_le_bytes(pixel[..4].try_into().unwrap()) as f64;
+                    assert!(
+                        (value - reference).abs() < 1e-4,
+                        "{size}->{target}, {lobes} lobes, scale {filter_scale}: {value} != {reference}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn custom_scale_is_lanczos_lobes() {
+    let pixels = (0..64 * 64)
+        .flat_map(|i| {
+            [
+                (i * 7 % 251) as u8,
+                (i * 13 % 241) as u8,
+                (i % 199) as u8,
+                255,
+            ]
+        })
+        .collect::<Vec<_>>();
+    let image = Image::new(&pixels, 64, 64, AlbedoFormat::Rgba8Unorm);
+
+    for lobes in [2.0, 3.0, 4.0] {
+        let options = DownsampleOptions::with_filter(Filter::Lanczos { lobes });
+        assert_eq!(
+            downsample_with_custom_scale(&image, 16, 16, lobes),
+            downsample_with_options(&image, 16, 16, &options)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use ispc_downsampler::{
+    downsample, downsample_normal_map, generate_mip_chain, generate_normal_map_mip_chain,
+    scale_alpha_to_original_coverage, try_generate_mip_chain, AlbedoFormat, DownsampleError, Image,
+    MipOptions, MipSource, NormalMapFormat, NormalMapMipOptions,
+};
+
+fn gradient(width: u32, height: u32) -> Vec<u8> {
+    (0..height)
+        .flat_map(|y| (0..width).flat_map(move |x| [(x * 7) as u8, (y * 11) as u8, (x + y) as u8]))
+        .collect()
+}
+
+#[test]
+fn odd_dimensions_are_rounded_down() {
+    let pixels = gradient(13, 5);
+    let image = Image::new(&pixels, 13, 5, AlbedoFormat::Rgb8Unorm);
+    let chain = generate_mip_chain(&image, &MipOptions::default());
+
+    let dimensions = chain
+        .levels()
+        .iter()
+        .map(|level| (level.width, level.height))
+        .collect::<Vec<_>>();
+    assert_eq!(dimensions, [(13, 5), (6, 2), (3, 1), (1, 1)]);
+
+    let mut offset = 0;
+    for level in chain.levels() {
+        assert_eq!(level.offset_in_bytes, offset);
+        assert_eq!(
+            level.size_in_bytes,
+            (level.width * level.height * 3) as usize
+        );
+        offset += level.size_in_bytes;
+    }
+    assert_eq!(chain.data().len(), offset);
+    assert_eq!(chain.level_data(0), &pixels[..]);
+}
+
+#[test]
+fn odd_dimension_levels_are_centered() {
+    // A horizontal ramp where every pixel stores its own x coordinate, so a filtered pixel stores the position it was
+    // sampled at. 61 pixels are rounded down to 30, which is a ratio of 61 / 30 rather than 2.
+    let pixels = (0..61 * 7)
+        .flat_map(|i| [(i % 61) as f32, 0.0, 0.0, 1.0])
+        .flat_map(f32::to_le_bytes)
+        .collect::<Vec<_>>();
+    let image = Image::new(&pixels, 61, 7, AlbedoFormat::Rgba32Float);
+    let chain = generate_mip_chain(&image, &MipOptions::default());
+    assert_eq!((chain.levels()[1].width, chain.levels()[1].height), (30, 3));
+
+    let ratio = 61.0 / 30.0;
+    let row = chain.level_data(1)[..30 * 16]
+        .chunks_exact(16)
+        .map(|pixel| f32::from_le_bytes(pixel[..4].try_into().unwrap()))
+        .collect::<Vec<_>>();
+    // Only the pixels whose footprint is not clipped by the border of the image reproduce the ramp.
+    for (x, &value) in row.iter().enumerate().take(26).skip(4) {
+        let center = (x as f32 + 0.5) * ratio - 0.5;
+        assert!(
+            (value - center).abs() < 0.1,
+            "pixel {x}: {value} != {center}"
+        );
+    }
+}
+
+#[test]
+fn levels_are_filtered_from_the_selected_source() {
+    let pixels = gradient(32, 16);
+    let image = Image::new(&pixels, 32, 16, AlbedoFormat::Rgb8Unorm);
+
+    let chain = generate_mip_chain(
+        &image,
+        &MipOptions {
+            source: MipSource::BaseImage,
+            ..Default::default()
+        },
+    );
+    assert_eq!(chain.levels().len(), 6);
+    for (index, level) in chain.levels().iter().enumerate().skip(1) {
+        assert_eq!(
+            chain.level_data(index),
+            downsample(&image, level.width, level.height)
+        );
+    }
+
+    let chain = generate_mip_chain(&image, &MipOptions::default());
+    for (index, level) in chain.levels().iter().enumerate().skip(1) {
+        assert_eq!(
+            chain.level_data(index),
+            downsample(&chain.level_image(index - 1), level.width, level.height)
+        );
+    }
+}
+
+#[test]
+fn strided_base_level_is_packed() {
+    let pixels = (0..4 * 4 * 4).map(|i| i as u8).collect::<Vec<_>>();
+    let image = Image::new_with_pixel_stride(&pixels, 4, 4, AlbedoFormat::Rgb8Unorm, 4);
+    let chain = generate_mip_chain(&image, &MipOptions::default());
+
+    let expected = pixels
+        .chunks_exact(4)
+        .flat_map(|pixel| &pixel[..3])
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(chain.level_data(0), &expected[..]);
+    assert_eq!(chain.levels().len(), 3);
+}
+
+#[test]
+fn alpha_coverage_matches_separate_rescaling() {
+    // Alpha-tested leaves, where a quarter of the texels is opaque.
+    let pixels = (0..64)
+        .flat_map(|y| (0..64).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let alpha = if (x / 4 + y / 4) % 4 == 0 { 255 } else { 0 };
+            [40, 160, 40, alpha]
+        })
+        .collect::<Vec<_>>();
+    let image = Image::new(&pixels, 64, 64, AlbedoFormat::Rgba8Unorm);
+
+    let chain = generate_mip_chain(
+        &image,
+        &MipOptions {
+            source: MipSource::BaseImage,
+            preserve_alpha_coverage: true,
+            alpha_cutoff: Some(0.5),
+            ..Default::default()
+        },
+    );
+
+    for (index, level) in chain.levels().iter().enumerate().skip(1) {
+        let downsampled = downsample(&image, level.width, level.height);
+        let expected = scale_alpha_to_original_coverage(
+            &image,
+            &Image::new(
+                &downsampled,
+                level.width,
+                level.height,
+                AlbedoFormat::Rgba8Unorm,
+            ),
+            Some(0.5),
+        );
+        assert_eq!(chain.level_data(index), &expected[..]);
+    }
+}
+
+#[test]
+fn alpha_coverage_requires_alpha_channel() {
+    let pixels = gradient(8, 8);
+    let image = Image::new(&pixels, 8, 8, AlbedoFormat::Rgb8Unorm);
+
+    assert_eq!(
+        try_generate_mip_chain(
+            &image,
+            &MipOptions {
+                preserve_alpha_coverage: true,
+                ..Default::default()
+            },
+        )
+        .err(),
+        Some(DownsampleError::NoAlphaChannel)
+    );
+}
+
+#[test]
+fn normal_map_variance_grows_with_diverging_normals() {
+    // Alternating columns of normals tilted 45 degrees to the left and right.
+    let pixels = (0..16)
+        .flat_map(|_| {
+            (0..16).map(|x| {
+                if x % 2 == 0 {
+                    [38, 128, 218]
+                } else {
+                    [218, 128, 218]
+                }
+            })
+        })
+        .flatten()
+        .collect::<Vec<u8>>();
+    let image = Image::new(&pixels, 16, 16, NormalMapFormat::Rgb8);
+    let chain = generate_normal_map_mip_chain(
+        &image,
+        &NormalMapMipOptions {
+            output_variance: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(chain.levels().len(), 5);
+    assert_eq!(
+        chain.level_data(1),
+        &downsample_normal_map(&image, 8, 8)[..]
+    );
+    assert!(chain.level_variance(0).unwrap().iter().all(|&v| v == 0.0));
+    for index in 1..chain.levels().len() {
+        // The variance is largest when the average of both normals points straight up with a length of cos(45 degrees).
+        // Footprints which contain more normals of one side are a bit longer.
+        let max_variance = (1.0 - 0.5f32.sqrt()) / 0.5f32.sqrt();
+        for &variance in chain.level_variance(index).unwrap() {
+            assert!(
+                (0.25..=max_variance + 1e-4).contains(&variance),
+                "{variance} is outside of [0.25, {max_variance}]"
+            );
+        }
+    }
+}
+
+#[test]
+fn flat_normal_map_has_no_variance() {
+    let pixels = [128u8, 128, 255].repeat(8 * 4);
+    let image = Image::new(&pixels, 8, 4, NormalMapFormat::Rgb8);
+    let chain = generate_normal_map_mip_chain(
+        &image,
+        &NormalMapMipOptions {
+            source: MipSource::BaseImage,
+            output_variance: true,
+        },
+    );
+
+    for index in 0..chain.levels().len() {
+        assert!(chain
+            .level_variance(index)
+            .unwrap()
+            .iter()
+            .all(|&v| v < 1e-4));
+    }
+    assert!(
+        generate_normal_map_mip_chain(&image, &NormalMapMipOptions::default())
+            .level_variance(1)
+            .is_none()
+    );
+}
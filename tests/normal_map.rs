@@ -0,0 +1,268 @@
+use ispc_downsampler::{
+    downsample_normal_map, downsample_normal_map_with_options, Image, NormalMapFilter,
+    NormalMapFormat, NormalMapOptions,
+};
+
+const LANCZOS: NormalMapOptions = NormalMapOptions {
+    filter: NormalMapFilter::Lanczos { lobes: 3.0 },
+};
+
+fn decode(pixel: &[u8]) -> [f32; 3] {
+    [0, 1, 2].map(|channel| pixel[channel] as f32 / 255.0 * 2.0 - 1.0)
+}
+
+fn length(normal: [f32; 3]) -> f32 {
+    normal.iter().map(|n| n * n).sum::<f32>().sqrt()
+}
+
+#[test]
+fn lanczos_keeps_flat_normals() {
+    let pixels = [128u8, 128, 255].repeat(32 * 32);
+    let image = Image::new(&pixels, 32, 32, NormalMapFormat::Rgb8);
+
+    let downsampled = downsample_normal_map_with_options(&image, 8, 8, &LANCZOS);
+    assert!(downsampled
+        .chunks_exact(3)
+        .all(|pixel| pixel == [128, 128, 255]));
+}
+
+#[test]
+fn lanczos_output_is_normalized_and_close_to_box_filter() {
+    // A smooth bump, so that both filters should produce similar normals.
+    let pixels = (0..64)
+        .flat_map(|y| (0..64).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let nx = ((x as f32 / 64.0) * std::f32::consts::TAU).sin() * 0.5;
+            let ny = ((y as f32 / 64.0) * std::f32::consts::TAU).cos() * 0.5;
+            let nz = (1.0 - nx * nx - ny * ny).sqrt();
+            [nx, ny, nz].map(|n| ((n * 0.5 + 0.5) * 255.0).round() as u8)
+        })
+        .collect::<Vec<_>>();
+    let image = Image::new(&pixels, 64, 64, NormalMapFormat::Rgb8);
+
+    let lanczos = downsample_normal_map_with_options(&image, 16, 16, &LANCZOS);
+    let boxed = downsample_normal_map(&image, 16, 16);
+    for (lanczos, boxed) in lanczos.chunks_exact(3).zip(boxed.chunks_exact(3)) {
+        let (lanczos, boxed) = (decode(lanczos), decode(boxed));
+        assert!((length(lanczos) - 1.0).abs() < 0.02);
+        let dot = lanczos.iter().zip(boxed).map(|(a, b)| a * b).sum::<f32>();
+        assert!(dot > 0.98, "{lanczos:?} and {boxed:?} diverge");
+    }
+}
+
+#[test]
+fn lanczos_handles_cancelling_normals() {
+    // Alternating columns of normals pointing left and right, which cancel out to a zero vector.
+    let pixels = (0..32)
+        .flat_map(|_| {
+            (0..32).map(|x| {
+                if x % 2 == 0 {
+                    [0u8, 128, 128]
+                } else {
+                    [255, 128, 128]
+                }
+            })
+        })
+        .flatten()
+        .collect::<Vec<u8>>();
+    let image = Image::new(&pixels, 32, 32, NormalMapFormat::Rgb8);
+
+    let downsampled = downsample_normal_map_with_options(&image, 16, 16, &LANCZOS);
+    for pixel in downsampled.chunks_exact(3) {
+        assert!(
+            (length(decode(pixel)) - 1.0).abs() < 0.02,
+            "{pixel:?} is not a unit normal"
+        );
+    }
+}
+
+/// Encodes `normal` in the memory representation of `format`.
+fn encode(normal: [f32; 3], format: NormalMapFormat) -> Vec<u8> {
+    if format.is_octahedral() {
+        let [x, y, z] = normal;
+        let sum = x.abs() + y.abs() + z.abs();
+        let (x, y) = (x / sum, y / sum);
+        let (x, y) = if z < 0.0 {
+            ((1.0 - y.abs()) * x.signum(), (1.0 - x.abs()) * y.signum())
+        } else {
+            (x, y)
+        };
+        return [x, y]
+            .iter()
+            .flat_map(|&n| {
+                let n = n * 0.5 + 0.5;
+                if format == NormalMapFormat::Octahedral8 {
+                    vec![(n * 255.0).round() as u8]
+                } else {
+                    ((n * 65535.0).round() as u16).to_le_bytes().to_vec()
+                }
+            })
+            .collect();
+    }
+
+    let channels = if format.reconstructs_z() { 2 } else { 3 };
+    normal[..channels]
+        .iter()
+        .flat_map(|&n| match format {
+            NormalMapFormat::Rgb8 | NormalMapFormat::Rg8TangentSpaceReconstructedZ => {
+                vec![((n * 0.5 + 0.5) * 255.0).round() as u8]
+            }
+            NormalMapFormat::Rgb16 | NormalMapFormat::Rg16TangentSpaceReconstructedZ => {
+                (((n * 0.5 + 0.5) * 65535.0).round() as u16)
+                    .to_le_bytes()
+                    .to_vec()
+            }
+            NormalMapFormat::Rgb8Snorm | NormalMapFormat::Rg8SnormTangentSpaceReconstructedZ => {
+                vec![(n * 127.0).round() as i8 as u8]
+            }
+            NormalMapFormat::Rgb16Snorm | NormalMapFormat::Rg16SnormTangentSpaceReconstructedZ => {
+                ((n * 32767.0).round() as i16).to_le_bytes().to_vec()
+            }
+            NormalMapFormat::Octahedral8 | NormalMapFormat::Octahedral16 => unreachable!(),
+        })
+        .collect()
+}
+
+/// Inverse of [`encode()`], reconstructing Z where needed.
+fn decode_in(pixel: &[u8], format: NormalMapFormat) -> [f32; 3] {
+    let channel = |i: usize| match format {
+        NormalMapFormat::Rgb8
+        | NormalMapFormat::Rg8TangentSpaceReconstructedZ
+        | NormalMapFormat::Octahedral8 => pixel[i] as f32 / 255.0 * 2.0 - 1.0,
+        NormalMapFormat::Rgb16
+        | NormalMapFormat::Rg16TangentSpaceReconstructedZ
+        | NormalMapFormat::Octahedral16 => {
+            u16::from_le_bytes([pixel[i * 2], pixel[i * 2 + 1]]) as f32 / 65535.0 * 2.0 - 1.0
+        }
+        NormalMapFormat::Rgb8Snorm | NormalMapFormat::Rg8SnormTangentSpaceReconstructedZ => {
+            pixel[i] as i8 as f32 / 127.0
+        }
+        NormalMapFormat::Rgb16Snorm | NormalMapFormat::Rg16SnormTangentSpaceReconstructedZ => {
+            i16::from_le_bytes([pixel[i * 2], pixel[i * 2 + 1]]) as f32 / 32767.0
+        }
+    };
+    let (x, y) = (channel(0), channel(1));
+    if format.is_octahedral() {
+        let z = 1.0 - x.abs() - y.abs();
+        let (x, y) = if z < 0.0 {
+            ((1.0 - y.abs()) * x.signum(), (1.0 - x.abs()) * y.signum())
+        } else {
+            (x, y)
+        };
+        return [x, y, z];
+    }
+    let z = if format.reconstructs_z() {
+        (1.0 - x * x - y * y).max(0.0).sqrt()
+    } else {
+        channel(2)
+    };
+    [x, y, z]
+}
+
+#[test]
+fn all_formats_produce_the_same_normals() {
+    let normals = (0..32)
+        .flat_map(|y| (0..32).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let nx = (x as f32 / 5.0).sin() * 0.4 - 0.2;
+            let ny = (y as f32 / 7.0).cos() * 0.4;
+            [nx, ny, (1.0 - nx * nx - ny * ny).sqrt()]
+        })
+        .collect::<Vec<_>>();
+
+    let reference_format = NormalMapFormat::Rgb16;
+    let reference_pixels = normals
+        .iter()
+        .flat_map(|n| encode(*n, reference_format))
+        .collect::<Vec<_>>();
+    let reference = downsample_normal_map(
+        &Image::new(&reference_pixels, 32, 32, reference_format),
+        8,
+        8,
+    );
+
+    for format in [
+        NormalMapFormat::Rgb8,
+        NormalMapFormat::Rg8TangentSpaceReconstructedZ,
+        NormalMapFormat::Rg16TangentSpaceReconstructedZ,
+        NormalMapFormat::Rgb8Snorm,
+        NormalMapFormat::Rg8SnormTangentSpaceReconstructedZ,
+        NormalMapFormat::Rgb16Snorm,
+        NormalMapFormat::Rg16SnormTangentSpaceReconstructedZ,
+        NormalMapFormat::Octahedral8,
+        NormalMapFormat::Octahedral16,
+    ] {
+        let pixels = normals
+            .iter()
+            .flat_map(|n| encode(*n, format))
+            .collect::<Vec<_>>();
+        let downsampled = downsample_normal_map(&Image::new(&pixels, 32, 32, format), 8, 8);
+
+        let pixel_size = downsampled.len() / 64;
+        for (pixel, reference) in downsampled
+            .chunks_exact(pixel_size)
+            .zip(reference.chunks_exact(6))
+        {
+            let normal = decode_in(pixel, format);
+            let reference = decode_in(reference, reference_format);
+            let dot = normal
+                .iter()
+                .zip(reference)
+                .map(|(a, b)| a * b)
+                .sum::<f32>()
+                / (length(normal) * length(reference));
+            assert!(dot > 0.999, "{format:?}: {normal:?} != {reference:?}");
+        }
+    }
+}
+
+#[test]
+fn octahedral_formats_match_rgb8() {
+    // Normals covering the whole sphere, so that the folded lower hemisphere is filtered as well.
+    let normals = (0..32)
+        .flat_map(|y| (0..32).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let theta = y as f32 / 32.0 * std::f32::consts::PI;
+            let phi = x as f32 / 32.0 * std::f32::consts::TAU;
+            [
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let rgb8_pixels = normals
+        .iter()
+        .flat_map(|n| encode(*n, NormalMapFormat::Rgb8))
+        .collect::<Vec<_>>();
+    let rgb8 = downsample_normal_map(
+        &Image::new(&rgb8_pixels, 32, 32, NormalMapFormat::Rgb8),
+        16,
+        16,
+    );
+
+    for format in [NormalMapFormat::Octahedral8, NormalMapFormat::Octahedral16] {
+        let pixels = normals
+            .iter()
+            .flat_map(|n| encode(*n, format))
+            .collect::<Vec<_>>();
+        let downsampled = downsample_normal_map(&Image::new(&pixels, 32, 32, format), 16, 16);
+
+        let pixel_size = downsampled.len() / (16 * 16);
+        for (pixel, reference) in downsampled
+            .chunks_exact(pixel_size)
+            .zip(rgb8.chunks_exact(3))
+        {
+            let normal = decode_in(pixel, format);
+            let reference = decode_in(reference, NormalMapFormat::Rgb8);
+            let dot = normal
+                .iter()
+                .zip(reference)
+                .map(|(a, b)| a * b)
+                .sum::<f32>()
+                / (length(normal) * length(reference));
+            assert!(dot > 0.999, "{format:?}: {normal:?} != {reference:?}");
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use ispc_downsampler::{
+    downsample, downsample_into, downsample_normal_map, AlbedoFormat, Image, ImageMut,
+    NormalMapFormat,
+};
+
+/// Returns a 32x32 RGBA8 image, tightly packed and with every pixel padded to 8 bytes.
+fn packed_and_padded() -> (Vec<u8>, Vec<u8>) {
+    let packed = (0..32 * 32 * 4)
+        .map(|i| (i * 7 % 251) as u8)
+        .collect::<Vec<_>>();
+    let padded = packed
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3], 1, 2, 3, 4])
+        .collect::<Vec<_>>();
+    (packed, padded)
+}
+
+#[test]
+fn output_is_tightly_packed_for_padded_source() {
+    let (packed, padded) = packed_and_padded();
+    let packed_image = Image::new(&packed, 32, 32, AlbedoFormat::Rgba8Unorm);
+    let padded_image = Image::new_with_pixel_stride(&padded, 32, 32, AlbedoFormat::Rgba8Unorm, 8);
+
+    let expected = downsample(&packed_image, 8, 8);
+    let downsampled = downsample(&padded_image, 8, 8);
+    assert_eq!(downsampled.len(), 8 * 8 * 4);
+    assert_eq!(downsampled, expected);
+}
+
+#[test]
+fn explicit_output_stride_and_row_pitch() {
+    let (packed, padded) = packed_and_padded();
+    let expected = downsample(&Image::new(&packed, 32, 32, AlbedoFormat::Rgba8Unorm), 8, 8);
+
+    // Every pixel takes 6 bytes and every row 64 bytes, the padding has to be left untouched.
+    let mut output = vec![0xAAu8; 7 * 64 + 8 * 6];
+    downsample_into(
+        &Image::new_with_pixel_stride(&padded, 32, 32, AlbedoFormat::Rgba8Unorm, 8),
+        &mut ImageMut::new_with_row_pitch(&mut output, 8, 8, AlbedoFormat::Rgba8Unorm, 6, 64),
+    );
+
+    for y in 0..8 {
+        let row = &output[y * 64..];
+        for x in 0..8 {
+            assert_eq!(&row[x * 6..x * 6 + 4], &expected[(y * 8 + x) * 4..][..4]);
+            assert_eq!(&row[x * 6 + 4..x * 6 + 6], [0xAA, 0xAA]);
+        }
+        if y < 7 {
+            assert!(row[8 * 6..64].iter().all(|&b| b == 0xAA));
+        }
+    }
+}
+
+#[test]
+fn normal_map_output_keeps_source_stride() {
+    let pixels = [128u8, 128, 255, 0].repeat(16 * 16);
+    let image = Image::new_with_pixel_stride(&pixels, 16, 16, NormalMapFormat::Rgb8, 4);
+
+    let downsampled = downsample_normal_map(&image, 4, 4);
+    assert_eq!(downsampled.len(), 4 * 4 * 4);
+    assert!(downsampled
+        .chunks_exact(4)
+        .all(|pixel| pixel == [128, 128, 255, 255]));
+}
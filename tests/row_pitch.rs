@@ -0,0 +1,97 @@
+use ispc_downsampler::{
+    downsample, downsample_normal_map, generate_mip_chain, scale_alpha_to_original_coverage,
+    AlbedoFormat, Image, MipOptions, NormalMapFormat,
+};
+
+const ROW_PITCH: usize = 256;
+
+/// Pads every row of the tightly packed `pixels` to [`ROW_PITCH`] bytes, like a GPU readback buffer.
+fn pad_rows(pixels: &[u8], row_size: usize) -> Vec<u8> {
+    pixels
+        .chunks_exact(row_size)
+        .flat_map(|row| {
+            let mut row = row.to_vec();
+            row.resize(ROW_PITCH, 0xCD);
+            row
+        })
+        .collect()
+}
+
+fn rgba_pixels(width: usize, height: usize) -> Vec<u8> {
+    (0..width * height)
+        .flat_map(|i| {
+            let (x, y) = (i % width, i / width);
+            [
+                (x * 5) as u8,
+                (y * 3) as u8,
+                (x ^ y) as u8,
+                if (x + y) % 3 == 0 { 255 } else { 40 },
+            ]
+        })
+        .collect()
+}
+
+#[test]
+fn downsample_honors_row_pitch() {
+    let packed = rgba_pixels(40, 24);
+    let padded = pad_rows(&packed, 40 * 4);
+    let packed_image = Image::new(&packed, 40, 24, AlbedoFormat::Rgba8Unorm);
+    let padded_image =
+        Image::new_with_row_pitch(&padded, 40, 24, AlbedoFormat::Rgba8Unorm, 4, ROW_PITCH);
+
+    assert_eq!(
+        downsample(&padded_image, 10, 6),
+        downsample(&packed_image, 10, 6)
+    );
+
+    let chain = generate_mip_chain(&padded_image, &MipOptions::default());
+    assert_eq!(chain.level_data(0), &packed[..]);
+}
+
+#[test]
+fn alpha_coverage_honors_row_pitch() {
+    let packed = rgba_pixels(40, 24);
+    let padded = pad_rows(&packed, 40 * 4);
+    let packed_image = Image::new(&packed, 40, 24, AlbedoFormat::Rgba8Unorm);
+    let padded_image =
+        Image::new_with_row_pitch(&padded, 40, 24, AlbedoFormat::Rgba8Unorm, 4, ROW_PITCH);
+
+    let downsampled = downsample(&packed_image, 10, 6);
+    let downsampled_padded = pad_rows(&downsampled, 10 * 4);
+
+    let expected = scale_alpha_to_original_coverage(
+        &packed_image,
+        &Image::new(&downsampled, 10, 6, AlbedoFormat::Rgba8Unorm),
+        Some(0.5),
+    );
+    let rescaled = scale_alpha_to_original_coverage(
+        &padded_image,
+        &Image::new_with_row_pitch(
+            &downsampled_padded,
+            10,
+            6,
+            AlbedoFormat::Rgba8Unorm,
+            4,
+            ROW_PITCH,
+        ),
+        Some(0.5),
+    );
+    assert_eq!(rescaled, pad_rows(&expected, 10 * 4));
+}
+
+#[test]
+fn normal_map_honors_row_pitch() {
+    let packed = (0..32 * 16)
+        .flat_map(|i| [(i % 32 * 8) as u8, 128, 200])
+        .collect::<Vec<_>>();
+    let padded = pad_rows(&packed, 32 * 3);
+
+    assert_eq!(
+        downsample_normal_map(
+            &Image::new_with_row_pitch(&padded, 32, 16, NormalMapFormat::Rgb8, 3, ROW_PITCH),
+            8,
+            4
+        ),
+        downsample_normal_map(&Image::new(&packed, 32, 16, NormalMapFormat::Rgb8), 8, 4)
+    );
+}
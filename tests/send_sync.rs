@@ -0,0 +1,33 @@
+use ispc_downsampler::{AlbedoFormat, DownsampleOptions, Downsampler, Image, NormalMapFormat};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn public_types_are_send_and_sync() {
+    assert_send_sync::<Downsampler>();
+    assert_send_sync::<DownsampleOptions>();
+    assert_send_sync::<Image<'_, AlbedoFormat>>();
+    assert_send_sync::<Image<'_, NormalMapFormat>>();
+}
+
+#[test]
+fn cloned_downsampler_can_be_used_from_other_threads() {
+    let pixels = (0..64 * 64 * 4).map(|i| i as u8).collect::<Vec<_>>();
+    let image = Image::new(&pixels, 64, 64, AlbedoFormat::Rgba8Unorm);
+    let downsampler = Downsampler::new(64, 64, 16, 16, &DownsampleOptions::default());
+
+    let expected = downsampler.clone().downsample(&image);
+    std::thread::scope(|scope| {
+        let workers = (0..4)
+            .map(|_| {
+                let mut downsampler = downsampler.clone();
+                let image = &image;
+                scope.spawn(move || downsampler.downsample(image))
+            })
+            .collect::<Vec<_>>();
+
+        for worker in workers {
+            assert_eq!(worker.join().unwrap(), expected);
+        }
+    });
+}
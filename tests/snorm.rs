@@ -0,0 +1,65 @@
+use ispc_downsampler::{downsample, scale_alpha_to_original_coverage, AlbedoFormat, Image};
+
+fn decode(value: u8) -> f32 {
+    (value as i8 as f32 / 127.0).max(-1.0)
+}
+
+#[test]
+fn minimum_values_decode_to_minus_one() {
+    // Both -128 and -127 are -1.0, which is written back as -127.
+    for minimum in [-128i8, -127] {
+        let pixels = vec![minimum as u8; 16 * 16 * 4];
+        let image = Image::new(&pixels, 16, 16, AlbedoFormat::Rgba8Snorm);
+        let downsampled = downsample(&image, 8, 8);
+        assert!(downsampled.iter().all(|&v| v as i8 == -127), "{minimum}");
+    }
+}
+
+#[test]
+fn negative_values_survive_filtering() {
+    for (format, channels) in [(AlbedoFormat::Rgb8Snorm, 3), (AlbedoFormat::Rgba8Snorm, 4)] {
+        // A horizontal ramp from -100 to -37, which stays negative everywhere.
+        let pixels = (0..32 * 32)
+            .flat_map(|i| vec![(-100 + (i % 32) * 2) as i8 as u8; channels])
+            .collect::<Vec<_>>();
+        let image = Image::new(&pixels, 32, 32, format);
+        let downsampled = downsample(&image, 16, 16);
+
+        for (i, pixel) in downsampled.chunks_exact(channels).enumerate() {
+            // The ramp is linear, so every target pixel is the average of the two source pixels it covers.
+            let expected = (-100 + (i % 16) as i32 * 4 + 1) as f32 / 127.0;
+            for &value in pixel {
+                assert!(
+                    (decode(value) - expected).abs() <= 1.5 / 127.0,
+                    "{format:?} pixel {i}: {} != {expected}",
+                    decode(value)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn negative_alpha_counts_as_transparent() {
+    // Half of the source is opaque and half is -1.0, which has to count as transparent rather than as negative coverage.
+    let pixels = (0..8 * 8)
+        .flat_map(|i| {
+            let (x, y) = (i % 8, i / 8);
+            let alpha = if (x + y) % 2 == 0 { 127 } else { -127i8 as u8 };
+            [0, 0, 0, alpha]
+        })
+        .collect::<Vec<_>>();
+    let image = Image::new(&pixels, 8, 8, AlbedoFormat::Rgba8Snorm);
+    let downsampled = [0, 0, 0, 32].repeat(4 * 4);
+    let downsampled = Image::new(&downsampled, 4, 4, AlbedoFormat::Rgba8Snorm);
+
+    // Without a cutoff the coverage is the average alpha, so the rescaled alpha has to be 0.5.
+    let rescaled = scale_alpha_to_original_coverage(&image, &downsampled, None);
+    for pixel in rescaled.chunks_exact(4) {
+        assert!(
+            (decode(pixel[3]) - 0.5).abs() < 0.02,
+            "{}",
+            decode(pixel[3])
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use ispc_downsampler::{downsample_with_options, AlbedoFormat, DownsampleOptions, Filter, Image};
+
+/// Returns a `size` x `size` checkerboard of single pixels with `channels` channels, alternating between 0 and 255.
+fn checkerboard(size: u32, channels: usize) -> Vec<u8> {
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .flat_map(|(x, y)| vec![if (x + y) % 2 == 0 { 0 } else { 255 }; channels])
+        .collect()
+}
+
+fn box_options() -> DownsampleOptions {
+    DownsampleOptions::with_filter(Filter::Box)
+}
+
+#[test]
+fn srgb_is_filtered_in_linear_space() {
+    let pixels = checkerboard(8, 3);
+    let image = Image::new(&pixels, 8, 8, AlbedoFormat::Srgb8);
+
+    // Every target pixel averages two black and two white pixels, which is 0.5 in linear light and 188 in sRGB.
+    let downsampled = downsample_with_options(&image, 4, 4, &box_options());
+    assert!(downsampled.iter().all(|&v| v == 188), "{downsampled:?}");
+
+    // Filtering the same values as unorm averages the encoded values instead.
+    let image = Image::new(&pixels, 8, 8, AlbedoFormat::Rgb8Unorm);
+    let downsampled = downsample_with_options(&image, 4, 4, &box_options());
+    assert!(downsampled.iter().all(|&v| v == 128), "{downsampled:?}");
+}
+
+#[test]
+fn srgba_alpha_is_linear() {
+    let pixels = checkerboard(8, 4);
+    let image = Image::new(&pixels, 8, 8, AlbedoFormat::Srgba8);
+
+    let downsampled = downsample_with_options(&image, 4, 4, &box_options());
+    for pixel in downsampled.chunks_exact(4) {
+        assert_eq!(pixel, [188, 188, 188, 128]);
+    }
+}
@@ -0,0 +1,153 @@
+use ispc_downsampler::{AlbedoFormat, DownsampleError, Image, ImageMut, NormalMapFormat};
+
+#[test]
+fn truncated_source_is_rejected() {
+    let pixels = vec![0u8; 16 * 16 * 4];
+
+    assert!(Image::try_new(&pixels, 16, 16, AlbedoFormat::Rgba8Unorm).is_ok());
+    assert_eq!(
+        Image::try_new(
+            &pixels[..pixels.len() - 1],
+            16,
+            16,
+            AlbedoFormat::Rgba8Unorm
+        )
+        .err(),
+        Some(DownsampleError::BufferTooSmall {
+            len_in_bytes: 16 * 16 * 4 - 1,
+            required_len_in_bytes: 16 * 16 * 4
+        })
+    );
+    assert!(matches!(
+        Image::try_new(&pixels, 16, 16, AlbedoFormat::Rgba16Unorm),
+        Err(DownsampleError::BufferTooSmall { .. })
+    ));
+    assert!(matches!(
+        Image::try_new(
+            &pixels[..16 * 16 * 2],
+            16,
+            16,
+            NormalMapFormat::Rg16TangentSpaceReconstructedZ
+        ),
+        Err(DownsampleError::BufferTooSmall { .. })
+    ));
+}
+
+#[test]
+fn last_pixel_does_not_need_full_stride() {
+    // Rgb8 pixels with a stride of 4 bytes, where the padding of the last pixel is cut off.
+    let pixels = vec![0u8; 8 * 8 * 4 - 1];
+
+    assert!(Image::try_new_with_pixel_stride(&pixels, 8, 8, AlbedoFormat::Rgb8Unorm, 4).is_ok());
+    assert!(matches!(
+        Image::try_new_with_pixel_stride(
+            &pixels[..pixels.len() - 1],
+            8,
+            8,
+            AlbedoFormat::Rgb8Unorm,
+            4
+        ),
+        Err(DownsampleError::BufferTooSmall { .. })
+    ));
+}
+
+#[test]
+fn invalid_stride_is_rejected() {
+    let pixels = vec![0u8; 16 * 16 * 4];
+
+    assert_eq!(
+        Image::try_new_with_pixel_stride(&pixels, 16, 16, AlbedoFormat::Rgba8Unorm, 3).err(),
+        Some(DownsampleError::PixelStrideTooSmall {
+            pixel_stride_in_bytes: 3,
+            pixel_size_in_bytes: 4
+        })
+    );
+    assert_eq!(
+        Image::try_new(&pixels, 0, 16, AlbedoFormat::Rgba8Unorm).err(),
+        Some(DownsampleError::EmptyImage {
+            width: 0,
+            height: 16
+        })
+    );
+}
+
+#[test]
+fn truncated_destination_is_rejected() {
+    let mut pixels = vec![0u8; 8 * 256];
+
+    // The last row only needs to hold its pixels, not the full pitch.
+    assert!(ImageMut::try_new_with_row_pitch(
+        &mut pixels[..7 * 256 + 8 * 4],
+        8,
+        8,
+        AlbedoFormat::Rgba8Unorm,
+        4,
+        256
+    )
+    .is_ok());
+    assert!(matches!(
+        ImageMut::try_new_with_row_pitch(
+            &mut pixels[..7 * 256 + 8 * 4 - 1],
+            8,
+            8,
+            AlbedoFormat::Rgba8Unorm,
+            4,
+            256
+        ),
+        Err(DownsampleError::BufferTooSmall { .. })
+    ));
+    assert_eq!(
+        ImageMut::try_new_with_row_pitch(&mut pixels, 8, 8, AlbedoFormat::Rgba8Unorm, 4, 16).err(),
+        Some(DownsampleError::RowPitchTooSmall {
+            row_pitch_in_bytes: 16,
+            row_size_in_bytes: 32
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "require at least")]
+fn panicking_constructor_rejects_truncated_source() {
+    let pixels = vec![0u8; 16 * 16 * 4 - 1];
+    Image::new(&pixels, 16, 16, AlbedoFormat::Rgba8Unorm);
+}
+
+#[test]
+fn downsampling_from_stride_padded_source_stays_in_bounds() {
+    // Every pixel is padded to 8 bytes, except for the last one.
+    let mut pixels = vec![0u8; 32 * 32 * 8 - 4];
+    for (i, pixel) in pixels.chunks_mut(8).enumerate() {
+        pixel[..4].fill(i as u8);
+    }
+    let image = Image::new_with_pixel_stride(&pixels, 32, 32, AlbedoFormat::Rgba8Unorm, 8);
+    let downsampled = ispc_downsampler::downsample(&image, 8, 8);
+    assert_eq!(downsampled.len(), 8 * 8 * 4);
+
+    let normal_map = Image::new_with_pixel_stride(&pixels, 32, 32, NormalMapFormat::Rgb8, 8);
+    let downsampled = ispc_downsampler::downsample_normal_map(&normal_map, 8, 8);
+    assert_eq!(downsampled.len(), 8 * 8 * 8);
+}
+
+#[test]
+fn overflowing_stride_is_rejected() {
+    let mut pixels = vec![0u8; 3 * 4];
+
+    assert_eq!(
+        Image::try_new_with_pixel_stride(&pixels, 3, 1, AlbedoFormat::Rgba8Unorm, usize::MAX / 2)
+            .err(),
+        Some(DownsampleError::BufferTooSmall {
+            len_in_bytes: 3 * 4,
+            required_len_in_bytes: usize::MAX
+        })
+    );
+    assert!(matches!(
+        ImageMut::try_new_with_pixel_stride(
+            &mut pixels,
+            3,
+            1,
+            AlbedoFormat::Rgba8Unorm,
+            usize::MAX / 2
+        ),
+        Err(DownsampleError::BufferTooSmall { .. })
+    ));
+}
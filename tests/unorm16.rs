@@ -0,0 +1,89 @@
+use ispc_downsampler::{downsample, scale_alpha_to_original_coverage, AlbedoFormat, Image};
+
+fn read_u16(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|value| u16::from_le_bytes([value[0], value[1]]))
+        .collect()
+}
+
+#[test]
+fn gradients_keep_16_bit_precision() {
+    for (format, channels) in [
+        (AlbedoFormat::R16Unorm, 1),
+        (AlbedoFormat::Rg16Unorm, 2),
+        (AlbedoFormat::Rgb16Unorm, 3),
+        (AlbedoFormat::Rgba16Unorm, 4),
+    ] {
+        // A ramp that only spans a few 8-bit steps, so quantizing to 8 bits would collapse it into a few bands.
+        let pixels = (0..64 * 64)
+            .flat_map(|i| vec![20000 + (i % 64) as u16 * 3; channels])
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        let image = Image::new(&pixels, 64, 64, format);
+        let downsampled = read_u16(&downsample(&image, 32, 32));
+
+        for (i, pixel) in downsampled.chunks_exact(channels).enumerate() {
+            // Every target pixel covers two source pixels of the linear ramp, so it is their average.
+            let expected = 20000.0 + ((i % 32) * 2) as f32 * 3.0 + 1.5;
+            for &value in pixel {
+                assert!(
+                    (value as f32 - expected).abs() <= 1.0,
+                    "{format:?} pixel {i}: {value} != {expected}"
+                );
+            }
+        }
+
+        // Every column keeps its own value instead of banding.
+        let first_row = downsampled
+            .chunks_exact(channels)
+            .take(32)
+            .map(|pixel| pixel[0])
+            .collect::<Vec<_>>();
+        assert!(first_row.windows(2).all(|w| w[0] < w[1]), "{format:?}");
+    }
+}
+
+#[test]
+fn alpha_coverage_matches_8_bit() {
+    // Thin opaque lines, which lose coverage when downsampled.
+    let alpha = (0..64 * 64)
+        .map(|i| {
+            let (x, y) = (i % 64, i / 64);
+            x % 8 == 0 || (x + y) % 11 == 0
+        })
+        .collect::<Vec<_>>();
+    let rgba8 = alpha
+        .iter()
+        .flat_map(|&opaque| [40, 80, 120, if opaque { 255 } else { 0 }])
+        .collect::<Vec<_>>();
+    let rgba16 = alpha
+        .iter()
+        .flat_map(|&opaque| [10280u16, 20560, 30840, if opaque { 65535 } else { 0 }])
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+
+    let rescale = |pixels: &[u8], format| {
+        let image = Image::new(pixels, 64, 64, format);
+        let downsampled = downsample(&image, 16, 16);
+        scale_alpha_to_original_coverage(
+            &image,
+            &Image::new(&downsampled, 16, 16, format),
+            Some(0.5),
+        )
+    };
+    let rescaled8 = rescale(&rgba8, AlbedoFormat::Rgba8Unorm);
+    let rescaled16 = read_u16(&rescale(&rgba16, AlbedoFormat::Rgba16Unorm));
+
+    for (pixel8, pixel16) in rescaled8.chunks_exact(4).zip(rescaled16.chunks_exact(4)) {
+        let (alpha8, alpha16) = (pixel8[3] as f32 / 255.0, pixel16[3] as f32 / 65535.0);
+        assert!((alpha8 - alpha16).abs() < 0.02, "{alpha8} != {alpha16}");
+    }
+    // The rescaling has to make a difference for this test to be meaningful.
+    let downsampled16 = read_u16(&downsample(
+        &Image::new(&rgba16, 64, 64, AlbedoFormat::Rgba16Unorm),
+        16,
+        16,
+    ));
+    assert_ne!(rescaled16, downsampled16);
+}